@@ -19,6 +19,21 @@ fn main() {
     });
     println!("computed in obfuscated block: {}", result);
 
+    println!("\n=== flattened control flow ===");
+    let flat = obf_flatten! {
+        let mut acc = 1u64;
+        for i in 1..=5 {
+            acc *= i;
+        }
+        if acc > 100 {
+            acc -= 100;
+        } else {
+            acc += 100;
+        }
+        acc
+    };
+    println!("computed in flattened block: {}", flat);
+
     println!("\n=== cascade encryption demo ===");
     let original = b"this is some sensitive data that needs protection";
     println!("original length: {}", original.len());
@@ -70,18 +85,18 @@ fn main() {
     }
 
     println!("\n=== hash comparisons ===");
-    let data1 = b"test data 1";
-    let data2 = b"test data 2";
+    const DATA1: &[u8] = b"test data 1";
+    const DATA2: &[u8] = b"test data 2";
     
-    let h1 = ct_xxhash!(data1);
-    let h2 = ct_xxhash!(data2);
+    let h1 = ct_xxhash!(DATA1);
+    let h2 = ct_xxhash!(DATA2);
     
     println!("hash of data1: {:#x}", h1);
     println!("hash of data2: {:#x}", h2);
     println!("hashes different: {}", h1 != h2);
 
     println!("\n=== obfuscated value storage ===");
-    let values = vec![
+    let values = [
         ObfuscatedValue::new(111u32),
         ObfuscatedValue::new(222u32),
         ObfuscatedValue::new(333u32),
@@ -110,6 +125,19 @@ fn main() {
     });
     println!("nested result: {}", nested);
 
+    println!("\n=== attribute obfuscation ===");
+    println!("license check: {} {}", check_license("LIC-1234"), check_license("nope"));
+
     println!("\nadvanced demo completed!");
 }
 
+#[obfuscate]
+fn check_license(key: &str) -> bool {
+    let prefix = "LIC-";
+    if key.starts_with(prefix) {
+        key.len() == 8
+    } else {
+        false
+    }
+}
+
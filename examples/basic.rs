@@ -31,6 +31,9 @@ fn main() {
     });
     println!("hidden call result: {}", result);
 
+    let routed = obf_call!(compute_sensitive(100));
+    println!("indirect call result: {}", routed);
+
     println!("\n=== obfuscated if ===");
     let x = 10;
     obf_if!(x > 5, {
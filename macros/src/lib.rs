@@ -0,0 +1,333 @@
+use proc_macro::TokenStream;
+use quote::{format_ident, quote, ToTokens};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::visit_mut::{self, VisitMut};
+use syn::{
+    parse_macro_input, parse_quote, Attribute, Block, Data, DeriveInput, Expr, ExprIf, Fields, GenericArgument, ItemFn,
+    Lit, Meta, Pat, Stmt, Token, Type, UnOp,
+};
+
+struct Options {
+    strings: bool,
+    consts: bool,
+    flow: bool,
+    level: Option<u8>,
+}
+
+impl Parse for Options {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let metas = Punctuated::<Meta, Token![,]>::parse_terminated(input)?;
+        let mut opts = Options { strings: false, consts: false, flow: false, level: None };
+        let mut any = false;
+        for meta in metas {
+            match &meta {
+                Meta::Path(p) if p.is_ident("strings") => opts.strings = true,
+                Meta::Path(p) if p.is_ident("consts") => opts.consts = true,
+                Meta::Path(p) if p.is_ident("flow") => opts.flow = true,
+                Meta::NameValue(nv) if nv.path.is_ident("level") => {
+                    opts.level = Some(match &nv.value {
+                        Expr::Lit(l) => match &l.lit {
+                            Lit::Int(i) => i.base10_parse()?,
+                            _ => return Err(syn::Error::new_spanned(&nv.value, "level must be an integer")),
+                        },
+                        _ => return Err(syn::Error::new_spanned(&nv.value, "level must be an integer")),
+                    });
+                    continue;
+                }
+                _ => return Err(syn::Error::new_spanned(meta, "expected `strings`, `consts`, `flow` or `level = N`")),
+            }
+            any = true;
+        }
+        if !any {
+            opts.strings = true;
+            opts.consts = true;
+            opts.flow = true;
+        }
+        Ok(opts)
+    }
+}
+
+fn take_no_obf(attrs: &mut Vec<Attribute>) -> bool {
+    let before = attrs.len();
+    attrs.retain(|a| !a.path().is_ident("no_obf"));
+    attrs.len() != before
+}
+
+fn expr_attrs(expr: &mut Expr) -> Option<&mut Vec<Attribute>> {
+    Some(match expr {
+        Expr::Array(e) => &mut e.attrs,
+        Expr::Assign(e) => &mut e.attrs,
+        Expr::Async(e) => &mut e.attrs,
+        Expr::Await(e) => &mut e.attrs,
+        Expr::Binary(e) => &mut e.attrs,
+        Expr::Block(e) => &mut e.attrs,
+        Expr::Call(e) => &mut e.attrs,
+        Expr::Cast(e) => &mut e.attrs,
+        Expr::Closure(e) => &mut e.attrs,
+        Expr::Field(e) => &mut e.attrs,
+        Expr::ForLoop(e) => &mut e.attrs,
+        Expr::If(e) => &mut e.attrs,
+        Expr::Index(e) => &mut e.attrs,
+        Expr::Lit(e) => &mut e.attrs,
+        Expr::Loop(e) => &mut e.attrs,
+        Expr::Match(e) => &mut e.attrs,
+        Expr::MethodCall(e) => &mut e.attrs,
+        Expr::Paren(e) => &mut e.attrs,
+        Expr::Reference(e) => &mut e.attrs,
+        Expr::Return(e) => &mut e.attrs,
+        Expr::Struct(e) => &mut e.attrs,
+        Expr::Tuple(e) => &mut e.attrs,
+        Expr::Unary(e) => &mut e.attrs,
+        Expr::Unsafe(e) => &mut e.attrs,
+        Expr::While(e) => &mut e.attrs,
+        _ => return None,
+    })
+}
+
+fn promotable(expr: &Expr) -> bool {
+    match expr {
+        Expr::Lit(_) | Expr::Path(_) => true,
+        Expr::Array(a) => a.elems.iter().all(promotable),
+        Expr::Tuple(t) => t.elems.iter().all(promotable),
+        Expr::Repeat(r) => promotable(&r.expr),
+        Expr::Paren(p) => promotable(&p.expr),
+        Expr::Group(g) => promotable(&g.expr),
+        Expr::Unary(u) => !matches!(u.op, UnOp::Deref(_)) && promotable(&u.expr),
+        Expr::Binary(b) => promotable(&b.left) && promotable(&b.right),
+        Expr::Cast(c) => promotable(&c.expr),
+        _ => false,
+    }
+}
+
+fn contains_let(expr: &Expr) -> bool {
+    match expr {
+        Expr::Let(_) => true,
+        Expr::Binary(b) => contains_let(&b.left) || contains_let(&b.right),
+        Expr::Paren(p) => contains_let(&p.expr),
+        _ => false,
+    }
+}
+
+struct Rewriter {
+    opts: Options,
+}
+
+impl Rewriter {
+    fn level(&self) -> proc_macro2::TokenStream {
+        match self.opts.level {
+            Some(level) => quote!(level = #level;),
+            None => quote!(),
+        }
+    }
+
+    fn rewrite_lit(&self, lit: &Lit) -> Option<Expr> {
+        match lit {
+            Lit::Str(s) if self.opts.strings => {
+                let level = self.level();
+                Some(parse_quote!({
+                    static __OBF_STR: ::rust_native_obf::string::StrCache = ::rust_native_obf::string::StrCache::new();
+                    __OBF_STR.get(|| ::rust_native_obf::obf_str!(#level #s).to_owned())
+                }))
+            }
+            Lit::Int(i) if self.opts.consts => {
+                if i.base10_parse::<u64>().is_err() {
+                    return None;
+                }
+                let level = self.level();
+                let suffix = i.suffix();
+                if suffix.is_empty() {
+                    Some(parse_quote!(::rust_native_obf::obf_const!(#level #i)))
+                } else {
+                    let ty = syn::Ident::new(suffix, i.span());
+                    let digits = syn::LitInt::new(i.base10_digits(), i.span());
+                    Some(parse_quote!(::rust_native_obf::obf_const!(#level #digits, #ty)))
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn rewrite_if(&self, e: &ExprIf) -> Expr {
+        let cond = &e.cond;
+        let then = &e.then_branch;
+        let level = self.level();
+        match &e.else_branch {
+            None => parse_quote!(::rust_native_obf::obf_if!(#level #cond, #then)),
+            Some((_, els)) => {
+                let els: Block = match &**els {
+                    Expr::Block(b) => b.block.clone(),
+                    other => parse_quote!({ #other }),
+                };
+                parse_quote!(::rust_native_obf::obf_if!(#level #cond, #then, #els))
+            }
+        }
+    }
+}
+
+impl VisitMut for Rewriter {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        if let Some(attrs) = expr_attrs(expr) {
+            if take_no_obf(attrs) {
+                return;
+            }
+        }
+        match expr {
+            Expr::Lit(l) => {
+                if let Some(new) = self.rewrite_lit(&l.lit) {
+                    *expr = new;
+                }
+            }
+            Expr::Unary(u) if matches!(u.op, UnOp::Neg(_)) && matches!(&*u.expr, Expr::Lit(_)) => {}
+            Expr::Reference(r) if r.mutability.is_none() && promotable(&r.expr) => {}
+            Expr::Repeat(r) => self.visit_expr_mut(&mut r.expr),
+            Expr::Const(_) | Expr::Macro(_) => {}
+            Expr::If(e) => {
+                visit_mut::visit_expr_if_mut(self, e);
+                if self.opts.flow && !contains_let(&e.cond) {
+                    *expr = self.rewrite_if(e);
+                }
+            }
+            Expr::Call(c) => {
+                visit_mut::visit_expr_call_mut(self, c);
+                if self.opts.flow {
+                    *expr = parse_quote!(::rust_native_obf::hidden_call!(#c));
+                }
+            }
+            _ => visit_mut::visit_expr_mut(self, expr),
+        }
+    }
+
+    fn visit_stmt_mut(&mut self, stmt: &mut Stmt) {
+        match stmt {
+            Stmt::Local(local) => {
+                if !take_no_obf(&mut local.attrs) {
+                    visit_mut::visit_local_mut(self, local);
+                }
+            }
+            Stmt::Item(_) => {}
+            _ => visit_mut::visit_stmt_mut(self, stmt),
+        }
+    }
+
+    fn visit_pat_mut(&mut self, _: &mut Pat) {}
+
+    fn visit_type_mut(&mut self, _: &mut Type) {}
+
+    fn visit_generic_argument_mut(&mut self, arg: &mut GenericArgument) {
+        if !matches!(arg, GenericArgument::Const(_)) {
+            visit_mut::visit_generic_argument_mut(self, arg);
+        }
+    }
+}
+
+#[proc_macro_attribute]
+pub fn obfuscate(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let opts = parse_macro_input!(attr as Options);
+    let mut func = parse_macro_input!(item as ItemFn);
+    let level = opts.level.unwrap_or(1);
+    if level > 0 {
+        let mut rewriter = Rewriter { opts };
+        rewriter.visit_block_mut(&mut func.block);
+    }
+    if level >= 2 {
+        let body = &func.block;
+        let wrapped: Block = if level >= 3 {
+            parse_quote!({ ::rust_native_obf::obf_bogus!(#body) })
+        } else {
+            parse_quote!({ ::rust_native_obf::obf_block!(#body) })
+        };
+        *func.block = wrapped;
+    }
+    func.into_token_stream().into()
+}
+
+#[proc_macro_derive(Obfuscated)]
+pub fn derive_obfuscated(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let fields = match &input.data {
+        Data::Struct(s) => match &s.fields {
+            Fields::Named(named) => &named.named,
+            _ => {
+                return syn::Error::new_spanned(&input.ident, "Obfuscated needs a struct with named fields")
+                    .to_compile_error()
+                    .into()
+            }
+        },
+        _ => {
+            return syn::Error::new_spanned(&input.ident, "Obfuscated can only be derived for structs")
+                .to_compile_error()
+                .into()
+        }
+    };
+
+    let vis = &input.vis;
+    let name = &input.ident;
+    let shadow = format_ident!("Obfuscated{}", name);
+    let shadow_name = shadow.to_string();
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let generics = &input.generics;
+
+    let names: Vec<_> = fields.iter().map(|f| f.ident.clone().unwrap()).collect();
+    let types: Vec<_> = fields.iter().map(|f| f.ty.clone()).collect();
+    let labels: Vec<_> = names.iter().map(|n| n.to_string()).collect();
+    let setters: Vec<_> = names.iter().map(|n| format_ident!("set_{}", n)).collect();
+
+    let mut bounds = where_clause.cloned().unwrap_or_else(|| parse_quote!(where));
+    for ty in &types {
+        bounds.predicates.push(parse_quote!(#ty: ::rust_native_obf::NoUninit));
+    }
+
+    quote! {
+        #vis struct #shadow #generics #bounds {
+            #(#names: ::rust_native_obf::ObfuscatedValue<#types>,)*
+        }
+
+        impl #impl_generics #shadow #ty_generics #bounds {
+            pub fn new(value: #name #ty_generics) -> Self {
+                Self {
+                    #(#names: ::rust_native_obf::ObfuscatedValue::new(value.#names),)*
+                }
+            }
+
+            pub fn into_inner(self) -> #name #ty_generics {
+                #name {
+                    #(#names: self.#names.get(),)*
+                }
+            }
+
+            #(
+                pub fn #names(&self) -> #types {
+                    self.#names.get()
+                }
+
+                pub fn #setters(&mut self, value: #types) {
+                    self.#names.set(value);
+                }
+            )*
+        }
+
+        impl #impl_generics From<#name #ty_generics> for #shadow #ty_generics #bounds {
+            fn from(value: #name #ty_generics) -> Self {
+                Self::new(value)
+            }
+        }
+
+        impl #impl_generics Clone for #shadow #ty_generics #bounds {
+            fn clone(&self) -> Self {
+                Self {
+                    #(#names: self.#names.clone(),)*
+                }
+            }
+        }
+
+        impl #impl_generics ::core::fmt::Debug for #shadow #ty_generics #bounds {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                f.debug_struct(#shadow_name)
+                    #(.field(#labels, &format_args!("<redacted>")))*
+                    .finish()
+            }
+        }
+    }
+    .into()
+}
@@ -0,0 +1,216 @@
+use core::fmt;
+
+use crate::cipher::Cascade;
+use crate::hash::siphash24;
+use crate::xor_stream;
+
+pub const BLOB_MAGIC: [u8; 4] = *b"NOBF";
+pub const BLOB_VERSION: u8 = 1;
+pub const HEADER_LEN: usize = 20;
+pub const MAC_LEN: usize = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum CipherId {
+    Xor = 1,
+    Cascade = 2,
+}
+
+impl CipherId {
+    pub fn from_u8(id: u8) -> Option<Self> {
+        match id {
+            1 => Some(Self::Xor),
+            2 => Some(Self::Cascade),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BlobError {
+    Truncated { needed: usize, got: usize },
+    BadMagic([u8; 4]),
+    UnsupportedVersion(u8),
+    UnknownCipher(u8),
+    TrailingData(usize),
+    MacMismatch,
+    EmptyKey,
+    TooLarge(usize),
+}
+
+impl fmt::Display for BlobError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Truncated { needed, got } => write!(f, "blob truncated: need {} bytes, got {}", needed, got),
+            Self::BadMagic(magic) => write!(f, "bad blob magic {:02x?}", magic),
+            Self::UnsupportedVersion(v) => write!(f, "unsupported blob version {}", v),
+            Self::UnknownCipher(id) => write!(f, "unknown blob cipher id {}", id),
+            Self::TrailingData(n) => write!(f, "{} trailing bytes after blob", n),
+            Self::MacMismatch => f.write_str("blob mac mismatch"),
+            Self::EmptyKey => f.write_str("blob key is empty"),
+            Self::TooLarge(n) => write!(f, "payload of {} bytes does not fit a blob", n),
+        }
+    }
+}
+
+impl std::error::Error for BlobError {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BlobHeader {
+    pub version: u8,
+    pub cipher: CipherId,
+    pub rounds: u8,
+    pub nonce: u64,
+    pub len: u32,
+}
+
+impl BlobHeader {
+    pub fn parse(blob: &[u8]) -> Result<Self, BlobError> {
+        if blob.len() < HEADER_LEN {
+            return Err(BlobError::Truncated { needed: HEADER_LEN, got: blob.len() });
+        }
+        let magic = [blob[0], blob[1], blob[2], blob[3]];
+        if magic != BLOB_MAGIC {
+            return Err(BlobError::BadMagic(magic));
+        }
+        if blob[4] != BLOB_VERSION {
+            return Err(BlobError::UnsupportedVersion(blob[4]));
+        }
+        let cipher = CipherId::from_u8(blob[5]).ok_or(BlobError::UnknownCipher(blob[5]))?;
+        let mut nonce = [0u8; 8];
+        nonce.copy_from_slice(&blob[8..16]);
+        let mut len = [0u8; 4];
+        len.copy_from_slice(&blob[16..20]);
+        Ok(Self {
+            version: blob[4],
+            cipher,
+            rounds: blob[6],
+            nonce: u64::from_le_bytes(nonce),
+            len: u32::from_le_bytes(len),
+        })
+    }
+
+    pub fn to_bytes(&self) -> [u8; HEADER_LEN] {
+        let mut out = [0u8; HEADER_LEN];
+        out[..4].copy_from_slice(&BLOB_MAGIC);
+        out[4] = self.version;
+        out[5] = self.cipher as u8;
+        out[6] = self.rounds;
+        out[8..16].copy_from_slice(&self.nonce.to_le_bytes());
+        out[16..20].copy_from_slice(&self.len.to_le_bytes());
+        out
+    }
+}
+
+pub(crate) fn mac(key: &[u8], data: &[u8]) -> u64 {
+    let k0 = siphash24(0x6d61635f6e6f6266, 0x6b6579305f6b6466, key);
+    let k1 = siphash24(0x6d61635f6e6f6266, 0x6b6579315f6b6466, key);
+    siphash24(k0, k1, data)
+}
+
+fn xor_key(key: &[u8], nonce: u64) -> Vec<u8> {
+    let nonce = nonce.to_le_bytes();
+    key.iter().enumerate().map(|(i, k)| k ^ nonce[i % 8]).collect()
+}
+
+fn encrypt(header: &BlobHeader, key: &[u8], data: &mut [u8]) {
+    match header.cipher {
+        CipherId::Xor => xor_stream(data, &xor_key(key, header.nonce)),
+        CipherId::Cascade => Cascade::new(key, header.nonce, header.rounds as usize).encrypt(data),
+    }
+}
+
+fn decrypt(header: &BlobHeader, key: &[u8], data: &mut [u8]) {
+    match header.cipher {
+        CipherId::Xor => xor_stream(data, &xor_key(key, header.nonce)),
+        CipherId::Cascade => Cascade::new(key, header.nonce, header.rounds as usize).decrypt(data),
+    }
+}
+
+pub struct BlobEncoder<'k> {
+    key: &'k [u8],
+    cipher: CipherId,
+    rounds: u8,
+    nonce: Option<u64>,
+}
+
+impl<'k> BlobEncoder<'k> {
+    pub fn new(key: &'k [u8]) -> Self {
+        Self { key, cipher: CipherId::Cascade, rounds: 4, nonce: None }
+    }
+
+    pub fn cipher(mut self, cipher: CipherId) -> Self {
+        self.cipher = cipher;
+        self
+    }
+
+    pub fn rounds(mut self, rounds: u8) -> Self {
+        self.rounds = rounds;
+        self
+    }
+
+    pub fn nonce(mut self, nonce: u64) -> Self {
+        self.nonce = Some(nonce);
+        self
+    }
+
+    pub fn encode(&self, data: &[u8]) -> Result<Vec<u8>, BlobError> {
+        if self.key.is_empty() {
+            return Err(BlobError::EmptyKey);
+        }
+        let len = u32::try_from(data.len()).map_err(|_| BlobError::TooLarge(data.len()))?;
+        let header = BlobHeader {
+            version: BLOB_VERSION,
+            cipher: self.cipher,
+            rounds: self.rounds,
+            nonce: self.nonce.unwrap_or_else(Cascade::random_nonce),
+            len,
+        };
+        let mut out = Vec::with_capacity(HEADER_LEN + data.len() + MAC_LEN);
+        out.extend_from_slice(&header.to_bytes());
+        out.extend_from_slice(data);
+        encrypt(&header, self.key, &mut out[HEADER_LEN..]);
+        let tag = mac(self.key, &out);
+        out.extend_from_slice(&tag.to_le_bytes());
+        Ok(out)
+    }
+}
+
+pub struct BlobDecoder<'k> {
+    key: &'k [u8],
+}
+
+impl<'k> BlobDecoder<'k> {
+    pub fn new(key: &'k [u8]) -> Self {
+        Self { key }
+    }
+
+    pub fn decode(&self, blob: &[u8]) -> Result<Vec<u8>, BlobError> {
+        self.decode_with_header(blob).map(|(_, data)| data)
+    }
+
+    pub fn decode_with_header(&self, blob: &[u8]) -> Result<(BlobHeader, Vec<u8>), BlobError> {
+        if self.key.is_empty() {
+            return Err(BlobError::EmptyKey);
+        }
+        let header = BlobHeader::parse(blob)?;
+        let (body_end, needed) = HEADER_LEN
+            .checked_add(header.len as usize)
+            .and_then(|end| Some((end, end.checked_add(MAC_LEN)?)))
+            .ok_or(BlobError::TooLarge(header.len as usize))?;
+        if blob.len() < needed {
+            return Err(BlobError::Truncated { needed, got: blob.len() });
+        }
+        if blob.len() > needed {
+            return Err(BlobError::TrailingData(blob.len() - needed));
+        }
+        let mut tag = [0u8; MAC_LEN];
+        tag.copy_from_slice(&blob[body_end..needed]);
+        if mac(self.key, &blob[..body_end]) != u64::from_le_bytes(tag) {
+            return Err(BlobError::MacMismatch);
+        }
+        let mut data = blob[HEADER_LEN..body_end].to_vec();
+        decrypt(&header, self.key, &mut data);
+        Ok((header, data))
+    }
+}
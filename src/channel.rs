@@ -0,0 +1,184 @@
+use core::fmt;
+use std::io::{self, Read, Write};
+
+use crate::cipher::Cascade;
+use crate::hash::siphash24;
+use crate::xor_stream;
+
+pub const MAX_FRAME: usize = 1 << 24;
+const FRAME_HEADER: usize = 12;
+const FRAME_ROUNDS: usize = 3;
+
+#[derive(Debug)]
+pub enum ChannelError {
+    Io(io::Error),
+    Handshake,
+    Replayed { expected: u64, got: u64 },
+    OutOfOrder { expected: u64, got: u64 },
+    BadMac,
+    FrameTooLarge(usize),
+}
+
+impl fmt::Display for ChannelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "channel io error: {}", e),
+            Self::Handshake => f.write_str("channel handshake failed"),
+            Self::Replayed { expected, got } => write!(f, "replayed frame {} (expected {})", got, expected),
+            Self::OutOfOrder { expected, got } => write!(f, "frame {} out of order (expected {})", got, expected),
+            Self::BadMac => f.write_str("frame mac mismatch"),
+            Self::FrameTooLarge(n) => write!(f, "frame of {} bytes exceeds the limit", n),
+        }
+    }
+}
+
+impl std::error::Error for ChannelError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ChannelError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+#[macro_export]
+macro_rules! channel_seed {
+    ($name:expr) => {{
+        const _SEED: u64 = $crate::rng::gen_entropy($name);
+        _SEED
+    }};
+}
+
+type Key = [u64; 2];
+
+fn derive(key: Key, label: u8, input: u64) -> u64 {
+    let mut data = [label; 9];
+    data[1..].copy_from_slice(&input.to_le_bytes());
+    siphash24(key[0], key[1], &data)
+}
+
+fn direction_key(seed: u64, from: u64, to: u64) -> Key {
+    let base = [seed, from];
+    [derive(base, b'd', to), derive(base, b'D', to)]
+}
+
+fn next_key(key: Key, tag: u64) -> Key {
+    [derive(key, b'r', tag), derive(key, b'R', tag)]
+}
+
+fn frame_mac(key: Key, frame: &[u8]) -> u64 {
+    siphash24(key[0], key[1], frame)
+}
+
+fn frame_cipher(key: Key, seq: u64) -> Cascade {
+    Cascade::from_seed(derive(key, b'c', seq), seq, FRAME_ROUNDS)
+}
+
+fn frame_pad(key: Key) -> [u8; 16] {
+    let mut pad = [0u8; 16];
+    pad[..8].copy_from_slice(&derive(key, b'p', 0).to_le_bytes());
+    pad[8..].copy_from_slice(&derive(key, b'p', 1).to_le_bytes());
+    pad
+}
+
+pub struct ObfChannel<S: Read + Write> {
+    stream: S,
+    send_key: Key,
+    recv_key: Key,
+    send_seq: u64,
+    recv_seq: u64,
+}
+
+impl<S: Read + Write> ObfChannel<S> {
+    pub fn handshake(mut stream: S, seed: u64) -> Result<Self, ChannelError> {
+        let local = Cascade::random_nonce();
+        let mask = siphash24(seed, 0x68656c6c6f, b"mask");
+        stream.write_all(&(local ^ mask).to_le_bytes())?;
+        stream.flush()?;
+        let mut buf = [0u8; 8];
+        stream.read_exact(&mut buf)?;
+        let peer = u64::from_le_bytes(buf) ^ mask;
+        if peer == local {
+            return Err(ChannelError::Handshake);
+        }
+        Ok(Self {
+            stream,
+            send_key: direction_key(seed, local, peer),
+            recv_key: direction_key(seed, peer, local),
+            send_seq: 0,
+            recv_seq: 0,
+        })
+    }
+
+    pub fn send(&mut self, msg: &[u8]) -> Result<(), ChannelError> {
+        if msg.len() > MAX_FRAME {
+            return Err(ChannelError::FrameTooLarge(msg.len()));
+        }
+        let key = self.send_key;
+        let mut frame = Vec::with_capacity(FRAME_HEADER + msg.len() + 8);
+        frame.extend_from_slice(&self.send_seq.to_le_bytes());
+        frame.extend_from_slice(&(msg.len() as u32).to_le_bytes());
+        frame.extend_from_slice(msg);
+        let body = &mut frame[FRAME_HEADER..];
+        frame_cipher(key, self.send_seq).encrypt(body);
+        xor_stream(body, &frame_pad(key));
+        let tag = frame_mac(key, &frame);
+        frame.extend_from_slice(&tag.to_le_bytes());
+        self.stream.write_all(&frame)?;
+        self.stream.flush()?;
+        self.send_key = next_key(key, tag);
+        self.send_seq += 1;
+        Ok(())
+    }
+
+    pub fn recv(&mut self) -> Result<Vec<u8>, ChannelError> {
+        let mut header = [0u8; FRAME_HEADER];
+        self.stream.read_exact(&mut header)?;
+        let mut seq = [0u8; 8];
+        seq.copy_from_slice(&header[..8]);
+        let seq = u64::from_le_bytes(seq);
+        let len = u32::from_le_bytes([header[8], header[9], header[10], header[11]]) as usize;
+        if len > MAX_FRAME {
+            return Err(ChannelError::FrameTooLarge(len));
+        }
+        let mut frame = vec![0u8; FRAME_HEADER + len + 8];
+        frame[..FRAME_HEADER].copy_from_slice(&header);
+        self.stream.read_exact(&mut frame[FRAME_HEADER..])?;
+
+        let expected = self.recv_seq;
+        if seq < expected {
+            return Err(ChannelError::Replayed { expected, got: seq });
+        }
+        if seq > expected {
+            return Err(ChannelError::OutOfOrder { expected, got: seq });
+        }
+        let key = self.recv_key;
+        let (body, tag) = frame.split_at(FRAME_HEADER + len);
+        let mut tag_bytes = [0u8; 8];
+        tag_bytes.copy_from_slice(tag);
+        let tag = u64::from_le_bytes(tag_bytes);
+        if frame_mac(key, body) != tag {
+            return Err(ChannelError::BadMac);
+        }
+        let mut msg = body[FRAME_HEADER..].to_vec();
+        xor_stream(&mut msg, &frame_pad(key));
+        frame_cipher(key, seq).decrypt(&mut msg);
+        self.recv_key = next_key(key, tag);
+        self.recv_seq += 1;
+        Ok(msg)
+    }
+
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
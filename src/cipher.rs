@@ -0,0 +1,201 @@
+use std::io::{self, Read, Write};
+
+use crate::rng::{runtime_key, xxhash_mix};
+
+#[derive(Clone)]
+pub struct Cascade {
+    round_keys: Vec<u64>,
+    nonce: u64,
+}
+
+impl Cascade {
+    pub fn new(key: &[u8], nonce: u64, rounds: usize) -> Self {
+        let mut k0 = 0x6361736361646530u64 ^ key.len() as u64;
+        let mut k1 = 0x9e3779b97f4a7c15u64;
+        for chunk in key.chunks(8) {
+            let mut word = [0u8; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            k0 = xxhash_mix(k0 ^ u64::from_le_bytes(word));
+            k1 = xxhash_mix(k1.rotate_left(17) ^ k0);
+        }
+        Self::from_parts(k0, k1, nonce, rounds)
+    }
+
+    pub fn from_seed(seed: u64, nonce: u64, rounds: usize) -> Self {
+        Self::from_parts(xxhash_mix(seed), xxhash_mix(!seed), nonce, rounds)
+    }
+
+    fn from_parts(k0: u64, k1: u64, nonce: u64, rounds: usize) -> Self {
+        let round_keys = (0..rounds as u64)
+            .map(|r| xxhash_mix(k0 ^ xxhash_mix(nonce ^ r.wrapping_mul(0x9e3779b97f4a7c15))) ^ k1.rotate_left(r as u32 % 64))
+            .collect();
+        Self { round_keys, nonce }
+    }
+
+    pub fn random_nonce() -> u64 {
+        runtime_key()
+    }
+
+    pub fn nonce(&self) -> u64 {
+        self.nonce
+    }
+
+    pub fn rounds(&self) -> usize {
+        self.round_keys.len()
+    }
+
+    pub fn encrypt(&self, data: &mut [u8]) {
+        self.encrypt_at(data, 0)
+    }
+
+    pub fn decrypt(&self, data: &mut [u8]) {
+        self.decrypt_at(data, 0)
+    }
+
+    pub fn encrypt_at(&self, data: &mut [u8], offset: u64) {
+        for &rk in &self.round_keys {
+            apply(data, offset, rk, |b, add, mask| b.wrapping_add(add) ^ mask);
+        }
+    }
+
+    pub fn decrypt_at(&self, data: &mut [u8], offset: u64) {
+        for &rk in self.round_keys.iter().rev() {
+            apply(data, offset, rk, |b, add, mask| (b ^ mask).wrapping_sub(add));
+        }
+    }
+}
+
+#[inline(always)]
+fn apply(data: &mut [u8], offset: u64, round_key: u64, f: impl Fn(u8, u8, u8) -> u8) {
+    let mut block = u64::MAX;
+    let mut word = 0u64;
+    for (i, byte) in data.iter_mut().enumerate() {
+        let pos = offset + i as u64;
+        if pos / 4 != block {
+            block = pos / 4;
+            word = xxhash_mix(round_key ^ block);
+        }
+        let lane = (pos % 4) as u32 * 8;
+        *byte = f(*byte, (word >> lane) as u8, (word >> (lane + 32)) as u8);
+    }
+}
+
+pub struct CascadeWriter<W: Write> {
+    inner: W,
+    cipher: Cascade,
+    offset: u64,
+    header: usize,
+    buf: Vec<u8>,
+}
+
+impl<W: Write> CascadeWriter<W> {
+    pub fn new(inner: W, key: &[u8], rounds: usize) -> Self {
+        let cipher = Cascade::new(key, Cascade::random_nonce(), rounds);
+        Self { inner, cipher, offset: 0, header: 0, buf: Vec::new() }
+    }
+
+    pub fn with_cipher(inner: W, cipher: Cascade) -> Self {
+        Self { inner, cipher, offset: 0, header: 8, buf: Vec::new() }
+    }
+
+    fn write_header(&mut self) -> io::Result<()> {
+        let nonce = self.cipher.nonce().to_le_bytes();
+        while self.header < nonce.len() {
+            match self.inner.write(&nonce[self.header..]) {
+                Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+                Ok(n) => self.header += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.write_header()?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for CascadeWriter<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.write_header()?;
+        self.buf.clear();
+        self.buf.extend_from_slice(data);
+        self.cipher.encrypt_at(&mut self.buf, self.offset);
+        let n = self.inner.write(&self.buf)?;
+        self.offset += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_header()?;
+        self.inner.flush()
+    }
+}
+
+pub struct CascadeReader<R: Read> {
+    inner: R,
+    key: Vec<u8>,
+    rounds: usize,
+    cipher: Option<Cascade>,
+    offset: u64,
+}
+
+impl<R: Read> CascadeReader<R> {
+    pub fn new(inner: R, key: &[u8], rounds: usize) -> Self {
+        Self { inner, key: key.to_vec(), rounds, cipher: None, offset: 0 }
+    }
+
+    pub fn with_cipher(inner: R, cipher: Cascade) -> Self {
+        Self { inner, key: Vec::new(), rounds: cipher.rounds(), cipher: Some(cipher), offset: 0 }
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    fn read_nonce(&mut self) -> io::Result<Option<u64>> {
+        let mut nonce = [0u8; 8];
+        let mut got = 0;
+        while got < nonce.len() {
+            match self.inner.read(&mut nonce[got..]) {
+                Ok(0) if got == 0 => return Ok(None),
+                Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(n) => got += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(Some(u64::from_le_bytes(nonce)))
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for CascadeReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if self.cipher.is_none() {
+            match self.read_nonce()? {
+                Some(nonce) => self.cipher = Some(Cascade::new(&self.key, nonce, self.rounds)),
+                None => return Ok(0),
+            }
+        }
+        let n = self.inner.read(buf)?;
+        if let Some(cipher) = &self.cipher {
+            cipher.decrypt_at(&mut buf[..n], self.offset);
+        }
+        self.offset += n as u64;
+        Ok(n)
+    }
+}
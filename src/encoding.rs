@@ -2,46 +2,126 @@ pub const fn mba_encode_u32(val: u32, key: u32) -> u32 {
     let a = val ^ key;
     let b = a.wrapping_mul(0x9e3779b1);
     let c = b.rotate_left(13);
-    let d = c ^ 0xdeadbeef;
-    d
+    c ^ 0xdeadbeef
 }
 
 pub const fn mba_decode_u32(val: u32, key: u32) -> u32 {
     let d = val ^ 0xdeadbeef;
     let c = d.rotate_right(13);
-    let b = c.wrapping_mul(0x61c88647);
-    let a = b ^ key;
-    a
+    let b = c.wrapping_mul(0x0e8b2f51);
+    b ^ key
 }
 
 pub const fn mba_encode_u64(val: u64, key: u64) -> u64 {
     let a = val ^ key;
     let b = a.wrapping_mul(0x517cc1b727220a95);
     let c = b.rotate_left(31);
-    let d = c ^ 0xcafebabe12345678;
-    d
+    c ^ 0xcafebabe12345678
 }
 
 pub const fn mba_decode_u64(val: u64, key: u64) -> u64 {
     let d = val ^ 0xcafebabe12345678;
     let c = d.rotate_right(31);
-    let b = c.wrapping_mul(0xbf58476d1ce4e5b9);
-    let a = b ^ key;
-    a
+    let b = c.wrapping_mul(0x2040003d780970bd);
+    b ^ key
 }
 
+pub const fn mba_encode_u32_rounds(mut val: u32, key: u32, rounds: u32) -> u32 {
+    let mut r = 0;
+    while r < rounds {
+        val = mba_encode_u32(val, key.rotate_left(r * 7) ^ r);
+        r += 1;
+    }
+    val
+}
+
+#[inline(always)]
+pub const fn mba_decode_u32_rounds(mut val: u32, key: u32, rounds: u32) -> u32 {
+    let mut r = rounds;
+    while r > 0 {
+        r -= 1;
+        val = mba_decode_u32(val, key.rotate_left(r * 7) ^ r);
+    }
+    val
+}
+
+pub const fn mba_encode_u64_rounds(mut val: u64, key: u64, rounds: u32) -> u64 {
+    let mut r = 0;
+    while r < rounds {
+        val = mba_encode_u64(val, key.rotate_left(r * 11) ^ r as u64);
+        r += 1;
+    }
+    val
+}
+
+#[inline(always)]
+pub const fn mba_decode_u64_rounds(mut val: u64, key: u64, rounds: u32) -> u64 {
+    let mut r = rounds;
+    while r > 0 {
+        r -= 1;
+        val = mba_decode_u64(val, key.rotate_left(r * 11) ^ r as u64);
+    }
+    val
+}
+
+#[cfg(not(feature = "passthrough"))]
 #[macro_export]
 macro_rules! obf_const {
-    ($val:expr, u32) => {{
+    (level = $lvl:expr; $val:expr, u32) => {{
+        const _ROUNDS: u32 = $crate::level::mba_rounds($lvl);
         const _KEY: u32 = $crate::ct_rand!(u32, stringify!($val));
-        const _ENC: u32 = $crate::encoding::mba_encode_u32($val, _KEY);
-        $crate::encoding::mba_decode_u32(_ENC, _KEY)
+        const _ENC: u32 = $crate::encoding::mba_encode_u32_rounds($val, _KEY, _ROUNDS);
+        $crate::encoding::mba_decode_u32_rounds(_ENC, _KEY, _ROUNDS)
+    }};
+    (level = $lvl:expr; $val:expr, u64) => {{
+        const _ROUNDS: u32 = $crate::level::mba_rounds($lvl);
+        const _KEY: u64 = $crate::ct_rand!(u64, stringify!($val));
+        const _ENC: u64 = $crate::encoding::mba_encode_u64_rounds($val, _KEY, _ROUNDS);
+        $crate::encoding::mba_decode_u64_rounds(_ENC, _KEY, _ROUNDS)
     }};
-    ($val:expr, u64) => {{
+    (level = $lvl:expr; $val:expr, $ty:ident) => {{
+        const _ROUNDS: u32 = $crate::level::mba_rounds($lvl);
         const _KEY: u64 = $crate::ct_rand!(u64, stringify!($val));
-        const _ENC: u64 = $crate::encoding::mba_encode_u64($val, _KEY);
-        $crate::encoding::mba_decode_u64(_ENC, _KEY)
+        const _ENC: u64 = $crate::encoding::mba_encode_u64_rounds($val as u64, _KEY, _ROUNDS);
+        $crate::encoding::mba_decode_u64_rounds(_ENC, _KEY, _ROUNDS) as $ty
     }};
+    (level = $lvl:expr; $val:literal) => {{
+        const _VAL: u64 = $val;
+        const _ROUNDS: u32 = $crate::level::mba_rounds($lvl);
+        const _KEY: u64 = $crate::ct_rand!(u64, stringify!($val));
+        const _ENC: u64 = $crate::encoding::mba_encode_u64_rounds(_VAL, _KEY, _ROUNDS);
+        let mut v = 0;
+        $crate::encoding::assign_decoded(&mut v, $crate::encoding::mba_decode_u64_rounds(_ENC, _KEY, _ROUNDS));
+        v
+    }};
+    ($val:expr, $ty:ident) => {
+        $crate::obf_const!(level = $crate::level::OBF_LEVEL; $val, $ty)
+    };
+    ($val:literal) => {
+        $crate::obf_const!(level = $crate::level::OBF_LEVEL; $val)
+    };
+}
+
+pub trait FromDecoded: Copy {
+    fn from_decoded(v: u64) -> Self;
+}
+
+macro_rules! impl_from_decoded {
+    ($($ty:ty),*) => {
+        $(impl FromDecoded for $ty {
+            #[inline(always)]
+            fn from_decoded(v: u64) -> Self {
+                v as $ty
+            }
+        })*
+    };
+}
+
+impl_from_decoded!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, u128, i128);
+
+#[inline(always)]
+pub fn assign_decoded<T: FromDecoded>(dst: &mut T, v: u64) {
+    *dst = T::from_decoded(v);
 }
 
 pub fn runtime_encode(val: usize) -> usize {
@@ -50,7 +130,7 @@ pub fn runtime_encode(val: usize) -> usize {
 }
 
 pub fn runtime_decode(val: usize) -> usize {
-    let key_inv = 0xf1bbcdcbfa53dcc9usize;
+    let key_inv = 0xf1de83e19937733dusize;
     (val ^ 0xdeadbeef).rotate_right(7).wrapping_mul(key_inv)
 }
 
@@ -17,24 +17,381 @@ pub const fn gen_flow_keys<const N: usize>(base: u32, stmts: &[&'static str; N])
     keys
 }
 
+#[inline(always)]
+pub fn encode_cond(c: bool, key: u32) -> u32 {
+    let m = (c as u32).wrapping_neg();
+    core::hint::black_box((m & key) | (!m & !key))
+}
+
+#[inline(always)]
+pub fn select_index(enc: u32, key: u32) -> usize {
+    (core::hint::black_box(enc ^ !key) >> 31) as usize & 1
+}
+
+pub fn junk<const N: usize, const SEED: u64>() {
+    let mut seed = SEED;
+    for _ in 0..N {
+        match seed % 3 {
+            0 => {
+                core::hint::black_box(crate::noise_loop((seed >> 8) as usize % 32 + 8));
+            }
+            1 => {
+                crate::stack_trash();
+                core::hint::black_box(crate::fake_compute(seed));
+            }
+            _ => {
+                core::hint::black_box(crate::fake_compute(seed.rotate_left(17)));
+            }
+        }
+        seed = crate::xxhash_mix(seed);
+    }
+}
+
+#[cfg(not(feature = "passthrough"))]
 #[macro_export]
 macro_rules! obf_block {
-    ($code:block) => {{
-        let choice = $crate::ct_rand!(u8) % 3;
-        match choice {
+    (level = $lvl:expr; $code:block) => {{
+        $crate::flow::junk::<{ $crate::level::junk_blocks($lvl) }, { $crate::ct_rand!(u64, "junk") }>();
+        $code
+    }};
+    ($code:block) => {
+        $crate::obf_block!(level = $crate::level::OBF_LEVEL; $code)
+    };
+}
+
+#[cfg(not(feature = "passthrough"))]
+#[macro_export]
+macro_rules! obf_bogus {
+    ($code:block) => {
+        $crate::obf_bogus!([] $code)
+    };
+    ([$($name:ident : $ty:ident = $val:expr),* $(,)?] $code:block) => {{
+        match if $crate::level::OBF_LEVEL == 0 { 3 } else { $crate::ct_rand!(u8, "bogus") % 3 } {
             0 => {
-                $crate::noise_loop(20);
-                $code
+                if !$crate::opaque_pred!("bogus_a") {
+                    $crate::__obf_decoy!(a [$($name : $ty = $val),*] $code)
+                } else if !$crate::opaque_pred!("bogus_b") {
+                    $crate::__obf_decoy!(b [$($name : $ty = $val),*] $code)
+                } else {
+                    $(const $name: $ty = $val;)*
+                    $code
+                }
             }
             1 => {
-                $crate::stack_trash();
-                $code
+                if !$crate::opaque_pred!("bogus_b") {
+                    $crate::__obf_decoy!(a [$($name : $ty = $val),*] $code)
+                } else if $crate::opaque_pred!("bogus_a") {
+                    $(const $name: $ty = $val;)*
+                    $code
+                } else {
+                    $crate::__obf_decoy!(b [$($name : $ty = $val),*] $code)
+                }
+            }
+            2 => {
+                if $crate::opaque_pred!("bogus_a") {
+                    if !$crate::opaque_pred!("bogus_b") {
+                        $crate::__obf_decoy!(b [$($name : $ty = $val),*] $code)
+                    } else {
+                        $(const $name: $ty = $val;)*
+                        $code
+                    }
+                } else {
+                    $crate::__obf_decoy!(a [$($name : $ty = $val),*] $code)
+                }
             }
             _ => {
-                $crate::fake_compute(42);
+                $(const $name: $ty = $val;)*
                 $code
             }
         }
     }};
 }
 
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __obf_decoy {
+    ($tag:ident [$($name:ident : $ty:ident = $val:expr),*] $code:block) => {{
+        $(const $name: $ty = $crate::__obf_mutate!($ty, $val, $crate::ct_rand!(u64, stringify!($tag), stringify!($name)));)*
+        ::core::hint::black_box($crate::fake_compute($crate::ct_rand!(u64, "decoy", stringify!($tag))));
+        $crate::stack_trash();
+        $code
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __obf_mutate {
+    (u8, $val:expr, $salt:expr) => { ($val) ^ (($salt | 1) as u8) };
+    (u16, $val:expr, $salt:expr) => { ($val) ^ (($salt | 1) as u16) };
+    (u32, $val:expr, $salt:expr) => { ($val) ^ (($salt | 1) as u32) };
+    (u64, $val:expr, $salt:expr) => { ($val) ^ ($salt | 1) };
+    (u128, $val:expr, $salt:expr) => { ($val) ^ (($salt | 1) as u128) };
+    (usize, $val:expr, $salt:expr) => { ($val) ^ (($salt | 1) as usize) };
+    (i8, $val:expr, $salt:expr) => { ($val) ^ (($salt | 1) as i8) };
+    (i16, $val:expr, $salt:expr) => { ($val) ^ (($salt | 1) as i16) };
+    (i32, $val:expr, $salt:expr) => { ($val) ^ (($salt | 1) as i32) };
+    (i64, $val:expr, $salt:expr) => { ($val) ^ (($salt | 1) as i64) };
+    (i128, $val:expr, $salt:expr) => { ($val) ^ (($salt | 1) as i128) };
+    (isize, $val:expr, $salt:expr) => { ($val) ^ (($salt | 1) as isize) };
+    ($other:ident, $val:expr, $salt:expr) => {
+        compile_error!(concat!("obf_bogus!: `", stringify!($other), "` is not an integer type, decoy constants must be integers"))
+    };
+}
+
+pub enum Step<T> {
+    Next(T),
+    Break,
+    Continue,
+}
+
+pub const fn flow_key<const N: usize>(labels: &[&'static str; N], keys: &[u32; N], path: &str) -> u32 {
+    let path = path.as_bytes();
+    let mut i = 0;
+    while i < N {
+        let label = labels[i].as_bytes();
+        if label.len() > path.len() && label[path.len()] == b'|' {
+            let mut j = 0;
+            while j < path.len() && label[j] == path[j] {
+                j += 1;
+            }
+            if j == path.len() {
+                return keys[i];
+            }
+        }
+        i += 1;
+    }
+    panic!("obf_flatten: unknown state")
+}
+
+pub const fn flow_keys_distinct<const N: usize>(keys: &[u32; N]) -> bool {
+    let mut i = 0;
+    while i < N {
+        let mut j = i + 1;
+        while j < N {
+            if keys[i] == keys[j] {
+                return false;
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+    true
+}
+
+#[cfg(not(feature = "passthrough"))]
+#[macro_export]
+macro_rules! obf_flatten {
+    (in loop; $($body:tt)*) => {
+        $crate::__obf_flatten!(@seq [reissue] [] [] [r] [exit] $($body)*)
+    };
+    ($($body:tt)*) => {
+        $crate::__obf_flatten!(@seq [trap] [] [] [r] [exit] $($body)*)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __obf_flatten {
+    (@seq $mode:tt [$($acc:tt)*] $stack:tt [$($path:tt)*] $cont:tt) => {
+        $crate::__obf_flatten!(@pop $mode [$($acc)* { [$($path)*] [()] $cont }] $stack)
+    };
+    (@seq $mode:tt $acc:tt $stack:tt $path:tt $cont:tt let $($rest:tt)*) => {
+        $crate::__obf_flatten!(@let $mode $acc $stack $path $cont let $($rest)*)
+    };
+    (@seq $mode:tt $acc:tt $stack:tt $path:tt $cont:tt if let $($rest:tt)*) => {
+        $crate::__obf_flatten!(@block $mode $acc $stack $path $cont [if let] $($rest)*)
+    };
+    (@seq $mode:tt $acc:tt $stack:tt $path:tt $cont:tt if $($rest:tt)*) => {
+        $crate::__obf_flatten!(@cond $mode $acc $stack $path $cont [] $($rest)*)
+    };
+    (@seq $mode:tt $acc:tt $stack:tt $path:tt $cont:tt for $($rest:tt)*) => {
+        $crate::__obf_flatten!(@block $mode $acc $stack $path $cont [for] $($rest)*)
+    };
+    (@seq $mode:tt $acc:tt $stack:tt $path:tt $cont:tt while $($rest:tt)*) => {
+        $crate::__obf_flatten!(@block $mode $acc $stack $path $cont [while] $($rest)*)
+    };
+    (@seq $mode:tt $acc:tt $stack:tt $path:tt $cont:tt loop $($rest:tt)*) => {
+        $crate::__obf_flatten!(@block $mode $acc $stack $path $cont [loop] $($rest)*)
+    };
+    (@seq $mode:tt $acc:tt $stack:tt $path:tt $cont:tt match $($rest:tt)*) => {
+        $crate::__obf_flatten!(@block $mode $acc $stack $path $cont [match] $($rest)*)
+    };
+    (@seq $mode:tt $acc:tt $stack:tt $path:tt $cont:tt unsafe $($rest:tt)*) => {
+        $crate::__obf_flatten!(@block $mode $acc $stack $path $cont [unsafe] $($rest)*)
+    };
+    (@seq $mode:tt $acc:tt $stack:tt $path:tt $cont:tt $l:lifetime : $($rest:tt)*) => {
+        $crate::__obf_flatten!(@block $mode $acc $stack $path $cont [$l:] $($rest)*)
+    };
+    (@seq $mode:tt $acc:tt $stack:tt $path:tt $cont:tt { $($g:tt)* } $($rest:tt)*) => {
+        $crate::__obf_flatten!(@block $mode $acc $stack $path $cont [] { $($g)* } $($rest)*)
+    };
+    (@seq $mode:tt $acc:tt $stack:tt $path:tt $cont:tt $($rest:tt)*) => {
+        $crate::__obf_flatten!(@plain $mode $acc $stack $path $cont $($rest)*)
+    };
+
+    (@pop $mode:tt $acc:tt [{ $path:tt $cont:tt $($rest:tt)* } $($stack:tt)*]) => {
+        $crate::__obf_flatten!(@seq $mode $acc [$($stack)*] $path $cont $($rest)*)
+    };
+    (@pop $mode:tt $acc:tt []) => {
+        $crate::__obf_flatten!(@emit $mode $acc)
+    };
+
+    (@let $mode:tt [$($acc:tt)*] $stack:tt [$($path:tt)*] $cont:tt $s:stmt ;) => {
+        $crate::__obf_flatten!(@pop $mode [$($acc)* { [$($path)*] [$s;] $cont }] $stack)
+    };
+    (@let $mode:tt [$($acc:tt)*] $stack:tt [$($path:tt)*] $cont:tt $s:stmt ; $($rest:tt)+) => {
+        $crate::__obf_flatten!(@pop $mode [$($acc)* { [$($path)*] [
+            $s;
+            $crate::__obf_flatten!(@seq $mode [] [] [$($path)* l] [exit] $($rest)*)
+        ] $cont }] $stack)
+    };
+
+    (@plain $mode:tt [$($acc:tt)*] $stack:tt [$($path:tt)*] $cont:tt $s:stmt ;) => {
+        $crate::__obf_flatten!(@pop $mode [$($acc)* { [$($path)*] [$s;] $cont }] $stack)
+    };
+    (@plain $mode:tt [$($acc:tt)*] $stack:tt [$($path:tt)*] $cont:tt $s:stmt ; $($rest:tt)*) => {
+        $crate::__obf_flatten!(@seq $mode [$($acc)* { [$($path)*] [$s;] [goto $($path)* s] }]
+            $stack [$($path)* s] $cont $($rest)*)
+    };
+    (@plain $mode:tt [$($acc:tt)*] $stack:tt [$($path:tt)*] $cont:tt $e:expr) => {
+        $crate::__obf_flatten!(@pop $mode [$($acc)* { [$($path)*] [$e] $cont }] $stack)
+    };
+
+    (@block $mode:tt $acc:tt $stack:tt $path:tt $cont:tt [$($b:tt)*] { $($g:tt)* } else $($rest:tt)*) => {
+        $crate::__obf_flatten!(@block $mode $acc $stack $path $cont [$($b)* { $($g)* } else] $($rest)*)
+    };
+    (@block $mode:tt [$($acc:tt)*] $stack:tt [$($path:tt)*] $cont:tt [$($b:tt)*] { $($g:tt)* }) => {
+        $crate::__obf_flatten!(@pop $mode [$($acc)* { [$($path)*] [$($b)* { $($g)* }] $cont }] $stack)
+    };
+    (@block $mode:tt [$($acc:tt)*] $stack:tt [$($path:tt)*] $cont:tt [$($b:tt)*] { $($g:tt)* } $(;)? $($rest:tt)*) => {
+        $crate::__obf_flatten!(@seq $mode [$($acc)* { [$($path)*] [$($b)* { $($g)* };] [goto $($path)* s] }]
+            $stack [$($path)* s] $cont $($rest)*)
+    };
+    (@block $mode:tt $acc:tt $stack:tt $path:tt $cont:tt [$($b:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::__obf_flatten!(@block $mode $acc $stack $path $cont [$($b)* $next] $($rest)*)
+    };
+
+    (@cond $mode:tt $acc:tt $stack:tt $path:tt $cont:tt $c:tt { $($t:tt)* } else if $($rest:tt)*) => {
+        $crate::__obf_flatten!(@elif $mode $acc $stack $path $cont $c [$($t)*] [if] $($rest)*)
+    };
+    (@cond $mode:tt $acc:tt $stack:tt $path:tt $cont:tt $c:tt { $($t:tt)* } else { $($e:tt)* } $($rest:tt)*) => {
+        $crate::__obf_flatten!(@branch $mode $acc $stack $path $cont $c [$($t)*] [$($e)*] $($rest)*)
+    };
+    (@cond $mode:tt $acc:tt $stack:tt $path:tt $cont:tt $c:tt { $($t:tt)* } $($rest:tt)*) => {
+        $crate::__obf_flatten!(@branch $mode $acc $stack $path $cont $c [$($t)*] [] $($rest)*)
+    };
+    (@cond $mode:tt $acc:tt $stack:tt $path:tt $cont:tt [$($c:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::__obf_flatten!(@cond $mode $acc $stack $path $cont [$($c)* $next] $($rest)*)
+    };
+
+    (@elif $mode:tt $acc:tt $stack:tt $path:tt $cont:tt $c:tt $t:tt [$($e:tt)*] { $($g:tt)* } else $($rest:tt)*) => {
+        $crate::__obf_flatten!(@elif $mode $acc $stack $path $cont $c $t [$($e)* { $($g)* } else] $($rest)*)
+    };
+    (@elif $mode:tt $acc:tt $stack:tt $path:tt $cont:tt $c:tt $t:tt [$($e:tt)*] { $($g:tt)* } $($rest:tt)*) => {
+        $crate::__obf_flatten!(@branch $mode $acc $stack $path $cont $c $t [$($e)* { $($g)* }] $($rest)*)
+    };
+    (@elif $mode:tt $acc:tt $stack:tt $path:tt $cont:tt $c:tt $t:tt [$($e:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::__obf_flatten!(@elif $mode $acc $stack $path $cont $c $t [$($e)* $next] $($rest)*)
+    };
+
+    (@branch $mode:tt [$($acc:tt)*] [$($stack:tt)*] [$($path:tt)*] $cont:tt [$($c:tt)*] [$($t:tt)*] [$($e:tt)*]) => {
+        $crate::__obf_flatten!(@seq $mode
+            [$($acc)* { [$($path)*] [$($c)*] [branch [$($path)* t] [$($path)* e]] }]
+            [{ [$($path)* e] $cont $($e)* } $($stack)*]
+            [$($path)* t] $cont $($t)*)
+    };
+    (@branch $mode:tt $acc:tt $stack:tt $path:tt $cont:tt $c:tt $t:tt $e:tt ; $($rest:tt)*) => {
+        $crate::__obf_flatten!(@join $mode $acc $stack $path $cont $c $t $e $($rest)*)
+    };
+    (@branch $mode:tt $acc:tt $stack:tt $path:tt $cont:tt $c:tt $t:tt $e:tt $($rest:tt)+) => {
+        $crate::__obf_flatten!(@join $mode $acc $stack $path $cont $c $t $e $($rest)+)
+    };
+    (@join $mode:tt [$($acc:tt)*] [$($stack:tt)*] [$($path:tt)*] $cont:tt [$($c:tt)*] [$($t:tt)*] [$($e:tt)*] $($rest:tt)*) => {
+        $crate::__obf_flatten!(@seq $mode
+            [$($acc)* { [$($path)*] [$($c)*] [branch [$($path)* t] [$($path)* e]] }]
+            [{ [$($path)* e] [goto $($path)* s] $($e)* } { [$($path)* s] $cont $($rest)* } $($stack)*]
+            [$($path)* t] [goto $($path)* s] $($t)*)
+    };
+
+    (@emit [$mode:ident] [$({ [$($path:tt)*] [$($code:tt)*] $next:tt })*]) => {{
+        const __N: usize = [$(stringify!($($path)*)),*].len();
+        const __LABELS: [&str; __N] = [$(concat!(stringify!($($path)*), "|", stringify!($($code)*))),*];
+        const __KEYS: [u32; __N] = $crate::flow::gen_flow_keys::<__N>(
+            $crate::ct_rand!(u32, "flatten") ^ $crate::hash::sdbm_hash(__LABELS[0]), &__LABELS);
+        const _: () = assert!($crate::flow::flow_keys_distinct(&__KEYS), "obf_flatten: state key collision");
+        let mut __state: u32 = core::hint::black_box(__KEYS[0]);
+        let __exit = loop {
+            match __state {
+                $(
+                    s if s == const { $crate::flow::flow_key(&__LABELS, &__KEYS, stringify!($($path)*)) } => {
+                        match $crate::__obf_flatten!(@run $mode $($code)*) {
+                            $crate::flow::Step::Next(v) => {
+                                $crate::__obf_flatten!(@next __state s v [$($path)*] $next)
+                            }
+                            $crate::flow::Step::Break => break $crate::flow::Step::Break,
+                            $crate::flow::Step::Continue => break $crate::flow::Step::Continue,
+                        }
+                    }
+                )*
+                _ => unreachable!(),
+            }
+        };
+        $crate::__obf_flatten!(@exit $mode __exit)
+    }};
+
+    (@run trap $($code:tt)*) => {{
+        #[allow(unused_labels, unreachable_code, clippy::diverging_sub_expression)]
+        let __value = '__obf_state: { { $($code)* } };
+        $crate::flow::Step::Next(__value)
+    }};
+    (@run reissue $($code:tt)*) => {{
+        let mut __pass = false;
+        let mut __step = $crate::flow::Step::Break;
+        loop {
+            if __pass {
+                __step = $crate::flow::Step::Continue;
+                break;
+            }
+            __pass = true;
+            #[allow(unreachable_code, clippy::diverging_sub_expression)]
+            {
+                let __value = { $($code)* };
+                __step = $crate::flow::Step::Next(__value);
+                break;
+            }
+        }
+        __step
+    }};
+
+    (@next $state:ident $s:ident $v:ident $path:tt [exit]) => {
+        break $crate::flow::Step::Next($v)
+    };
+    (@next $state:ident $s:ident $v:ident [$($path:tt)*] [goto $($to:tt)*]) => {{
+        let _ = $v;
+        $state = $s ^ const {
+            $crate::flow::flow_key(&__LABELS, &__KEYS, stringify!($($path)*))
+                ^ $crate::flow::flow_key(&__LABELS, &__KEYS, stringify!($($to)*))
+        };
+    }};
+    (@next $state:ident $s:ident $v:ident [$($path:tt)*] [branch [$($t:tt)*] [$($e:tt)*]]) => {{
+        let c: bool = $v;
+        let m = (c as u32).wrapping_neg();
+        let here = const { $crate::flow::flow_key(&__LABELS, &__KEYS, stringify!($($path)*)) };
+        $state = $s ^ ((here ^ const { $crate::flow::flow_key(&__LABELS, &__KEYS, stringify!($($t)*)) }) & m
+            | (here ^ const { $crate::flow::flow_key(&__LABELS, &__KEYS, stringify!($($e)*)) }) & !m);
+    }};
+
+    (@exit trap $exit:ident) => {
+        match $exit {
+            $crate::flow::Step::Next(v) => v,
+            _ => unreachable!(),
+        }
+    };
+    (@exit reissue $exit:ident) => {
+        match $exit {
+            $crate::flow::Step::Next(v) => v,
+            $crate::flow::Step::Break => break,
+            $crate::flow::Step::Continue => continue,
+        }
+    };
+}
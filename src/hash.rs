@@ -30,15 +30,359 @@ pub const fn xxhash32(data: &[u8], seed: u32) -> u32 {
     h
 }
 
+const XXH32_P1: u32 = 0x9e3779b1;
+const XXH32_P2: u32 = 0x85ebca77;
+const XXH32_P3: u32 = 0xc2b2ae3d;
+const XXH32_P4: u32 = 0x27d4eb2f;
+const XXH32_P5: u32 = 0x165667b1;
+
+const fn read_u32(data: &[u8], i: usize) -> u32 {
+    u32::from_le_bytes([data[i], data[i + 1], data[i + 2], data[i + 3]])
+}
+
+const fn read_u64(data: &[u8], i: usize) -> u64 {
+    u64::from_le_bytes([
+        data[i], data[i + 1], data[i + 2], data[i + 3],
+        data[i + 4], data[i + 5], data[i + 6], data[i + 7],
+    ])
+}
+
+#[derive(Clone, Copy)]
+enum Input<'a> {
+    Bytes(&'a [u8], bool),
+    Wide(&'a [u16], bool),
+}
+
+const fn fold_unit(u: u16, fold: bool) -> u16 {
+    if fold && u >= b'A' as u16 && u <= b'Z' as u16 { u + 32 } else { u }
+}
+
+impl Input<'_> {
+    const fn len(self) -> usize {
+        match self {
+            Input::Bytes(data, _) => data.len(),
+            Input::Wide(data, _) => data.len() * 2,
+        }
+    }
+
+    const fn byte(self, i: usize) -> u8 {
+        match self {
+            Input::Bytes(data, fold) => fold_unit(data[i] as u16, fold) as u8,
+            Input::Wide(data, fold) => (fold_unit(data[i / 2], fold) >> (i % 2 * 8)) as u8,
+        }
+    }
+
+    const fn u32_at(self, i: usize) -> u32 {
+        match self {
+            Input::Bytes(data, false) => read_u32(data, i),
+            _ => u32::from_le_bytes([self.byte(i), self.byte(i + 1), self.byte(i + 2), self.byte(i + 3)]),
+        }
+    }
+}
+
+const fn xxh32_round(acc: u32, lane: u32) -> u32 {
+    acc.wrapping_add(lane.wrapping_mul(XXH32_P2)).rotate_left(13).wrapping_mul(XXH32_P1)
+}
+
+pub const fn xxh32(data: &[u8], seed: u32) -> u32 {
+    xxh32_input(Input::Bytes(data, false), seed)
+}
+
+pub const fn xxh32_ci(data: &[u8], seed: u32) -> u32 {
+    xxh32_input(Input::Bytes(data, true), seed)
+}
+
+pub const fn xxh32_utf16(data: &[u16], seed: u32) -> u32 {
+    xxh32_input(Input::Wide(data, false), seed)
+}
+
+pub const fn xxh32_utf16_ci(data: &[u16], seed: u32) -> u32 {
+    xxh32_input(Input::Wide(data, true), seed)
+}
+
+const fn xxh32_input(data: Input, seed: u32) -> u32 {
+    let len = data.len();
+    let mut i = 0;
+    let mut h = if len >= 16 {
+        let mut v1 = seed.wrapping_add(XXH32_P1).wrapping_add(XXH32_P2);
+        let mut v2 = seed.wrapping_add(XXH32_P2);
+        let mut v3 = seed;
+        let mut v4 = seed.wrapping_sub(XXH32_P1);
+        while i + 16 <= len {
+            v1 = xxh32_round(v1, data.u32_at(i));
+            v2 = xxh32_round(v2, data.u32_at(i + 4));
+            v3 = xxh32_round(v3, data.u32_at(i + 8));
+            v4 = xxh32_round(v4, data.u32_at(i + 12));
+            i += 16;
+        }
+        v1.rotate_left(1)
+            .wrapping_add(v2.rotate_left(7))
+            .wrapping_add(v3.rotate_left(12))
+            .wrapping_add(v4.rotate_left(18))
+    } else {
+        seed.wrapping_add(XXH32_P5)
+    };
+    h = h.wrapping_add(len as u32);
+
+    while i + 4 <= len {
+        h = h.wrapping_add(data.u32_at(i).wrapping_mul(XXH32_P3));
+        h = h.rotate_left(17).wrapping_mul(XXH32_P4);
+        i += 4;
+    }
+    while i < len {
+        h = h.wrapping_add((data.byte(i) as u32).wrapping_mul(XXH32_P5));
+        h = h.rotate_left(11).wrapping_mul(XXH32_P1);
+        i += 1;
+    }
+
+    h ^= h >> 15;
+    h = h.wrapping_mul(XXH32_P2);
+    h ^= h >> 13;
+    h = h.wrapping_mul(XXH32_P3);
+    h ^= h >> 16;
+    h
+}
+
+const XXH64_P1: u64 = 0x9e3779b185ebca87;
+const XXH64_P2: u64 = 0xc2b2ae3d27d4eb4f;
+const XXH64_P3: u64 = 0x165667b19e3779f9;
+const XXH64_P4: u64 = 0x85ebca77c2b2ae63;
+const XXH64_P5: u64 = 0x27d4eb2f165667c5;
+
+const fn xxh64_round(acc: u64, lane: u64) -> u64 {
+    acc.wrapping_add(lane.wrapping_mul(XXH64_P2)).rotate_left(31).wrapping_mul(XXH64_P1)
+}
+
+const fn xxh64_merge(acc: u64, v: u64) -> u64 {
+    (acc ^ xxh64_round(0, v)).wrapping_mul(XXH64_P1).wrapping_add(XXH64_P4)
+}
+
+pub const fn xxh64(data: &[u8], seed: u64) -> u64 {
+    let len = data.len();
+    let mut i = 0;
+    let mut h = if len >= 32 {
+        let mut v1 = seed.wrapping_add(XXH64_P1).wrapping_add(XXH64_P2);
+        let mut v2 = seed.wrapping_add(XXH64_P2);
+        let mut v3 = seed;
+        let mut v4 = seed.wrapping_sub(XXH64_P1);
+        while i + 32 <= len {
+            v1 = xxh64_round(v1, read_u64(data, i));
+            v2 = xxh64_round(v2, read_u64(data, i + 8));
+            v3 = xxh64_round(v3, read_u64(data, i + 16));
+            v4 = xxh64_round(v4, read_u64(data, i + 24));
+            i += 32;
+        }
+        let mut acc = v1.rotate_left(1)
+            .wrapping_add(v2.rotate_left(7))
+            .wrapping_add(v3.rotate_left(12))
+            .wrapping_add(v4.rotate_left(18));
+        acc = xxh64_merge(acc, v1);
+        acc = xxh64_merge(acc, v2);
+        acc = xxh64_merge(acc, v3);
+        xxh64_merge(acc, v4)
+    } else {
+        seed.wrapping_add(XXH64_P5)
+    };
+    h = h.wrapping_add(len as u64);
+
+    while i + 8 <= len {
+        h ^= xxh64_round(0, read_u64(data, i));
+        h = h.rotate_left(27).wrapping_mul(XXH64_P1).wrapping_add(XXH64_P4);
+        i += 8;
+    }
+    if i + 4 <= len {
+        h ^= (read_u32(data, i) as u64).wrapping_mul(XXH64_P1);
+        h = h.rotate_left(23).wrapping_mul(XXH64_P2).wrapping_add(XXH64_P3);
+        i += 4;
+    }
+    while i < len {
+        h ^= (data[i] as u64).wrapping_mul(XXH64_P5);
+        h = h.rotate_left(11).wrapping_mul(XXH64_P1);
+        i += 1;
+    }
+
+    h ^= h >> 33;
+    h = h.wrapping_mul(XXH64_P2);
+    h ^= h >> 29;
+    h = h.wrapping_mul(XXH64_P3);
+    h ^= h >> 32;
+    h
+}
+
+const fn sip_round(v: &mut [u64; 4]) {
+    v[0] = v[0].wrapping_add(v[1]);
+    v[1] = v[1].rotate_left(13) ^ v[0];
+    v[0] = v[0].rotate_left(32);
+    v[2] = v[2].wrapping_add(v[3]);
+    v[3] = v[3].rotate_left(16) ^ v[2];
+    v[0] = v[0].wrapping_add(v[3]);
+    v[3] = v[3].rotate_left(21) ^ v[0];
+    v[2] = v[2].wrapping_add(v[1]);
+    v[1] = v[1].rotate_left(17) ^ v[2];
+    v[2] = v[2].rotate_left(32);
+}
+
+const fn sip_block(v: &mut [u64; 4], m: u64) {
+    v[3] ^= m;
+    sip_round(v);
+    sip_round(v);
+    v[0] ^= m;
+}
+
+pub const fn siphash24(k0: u64, k1: u64, data: &[u8]) -> u64 {
+    let mut v = [
+        k0 ^ 0x736f6d6570736575,
+        k1 ^ 0x646f72616e646f6d,
+        k0 ^ 0x6c7967656e657261,
+        k1 ^ 0x7465646279746573,
+    ];
+    let len = data.len();
+    let mut i = 0;
+    while i + 8 <= len {
+        sip_block(&mut v, read_u64(data, i));
+        i += 8;
+    }
+    let mut last = (len as u64) << 56;
+    let mut j = 0;
+    while i + j < len {
+        last |= (data[i + j] as u64) << (j * 8);
+        j += 1;
+    }
+    sip_block(&mut v, last);
+    v[2] ^= 0xff;
+    sip_round(&mut v);
+    sip_round(&mut v);
+    sip_round(&mut v);
+    sip_round(&mut v);
+    v[0] ^ v[1] ^ v[2] ^ v[3]
+}
+
+pub const fn fnv1a32(data: &[u8]) -> u32 {
+    let mut h = 0x811c9dc5u32;
+    let mut i = 0;
+    while i < data.len() {
+        h ^= data[i] as u32;
+        h = h.wrapping_mul(0x01000193);
+        i += 1;
+    }
+    h
+}
+
+pub const fn fnv1a64(data: &[u8]) -> u64 {
+    let mut h = 0xcbf29ce484222325u64;
+    let mut i = 0;
+    while i < data.len() {
+        h ^= data[i] as u64;
+        h = h.wrapping_mul(0x00000100000001b3);
+        i += 1;
+    }
+    h
+}
+
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 { 0xedb88320 ^ (c >> 1) } else { c >> 1 };
+            k += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+};
+
+pub const fn crc32(data: &[u8]) -> u32 {
+    let mut c = 0xffffffffu32;
+    let mut i = 0;
+    while i < data.len() {
+        c = CRC32_TABLE[((c ^ data[i] as u32) & 0xff) as usize] ^ (c >> 8);
+        i += 1;
+    }
+    !c
+}
+
+const fn murmur3_scramble(k: u32) -> u32 {
+    k.wrapping_mul(0xcc9e2d51).rotate_left(15).wrapping_mul(0x1b873593)
+}
+
+pub const fn murmur3_32(data: &[u8], seed: u32) -> u32 {
+    let len = data.len();
+    let mut h = seed;
+    let mut i = 0;
+    while i + 4 <= len {
+        h ^= murmur3_scramble(read_u32(data, i));
+        h = h.rotate_left(13).wrapping_mul(5).wrapping_add(0xe6546b64);
+        i += 4;
+    }
+    let mut k = 0u32;
+    let mut j = len;
+    while j > i {
+        j -= 1;
+        k = (k << 8) | data[j] as u32;
+    }
+    if i < len {
+        h ^= murmur3_scramble(k);
+    }
+    h ^= len as u32;
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x85ebca6b);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0xc2b2ae35);
+    h ^= h >> 16;
+    h
+}
+
+pub const fn djb2(data: &[u8]) -> u32 {
+    let mut h = 5381u32;
+    let mut i = 0;
+    while i < data.len() {
+        h = h.wrapping_shl(5).wrapping_add(h).wrapping_add(data[i] as u32);
+        i += 1;
+    }
+    h
+}
+
+const fn sdbm_step(hash: u32, unit: u32) -> u32 {
+    unit.wrapping_add(hash.wrapping_shl(6))
+        .wrapping_add(hash.wrapping_shl(16))
+        .wrapping_sub(hash)
+}
+
 pub const fn sdbm_hash(s: &str) -> u32 {
-    let bytes = s.as_bytes();
+    sdbm_bytes(s.as_bytes(), false)
+}
+
+pub const fn sdbm_hash_ci(s: &str) -> u32 {
+    sdbm_bytes(s.as_bytes(), true)
+}
+
+const fn sdbm_bytes(bytes: &[u8], fold: bool) -> u32 {
     let mut hash = 0u32;
     let mut i = 0;
     while i < bytes.len() {
-        hash = (bytes[i] as u32)
-            .wrapping_add(hash.wrapping_shl(6))
-            .wrapping_add(hash.wrapping_shl(16))
-            .wrapping_sub(hash);
+        hash = sdbm_step(hash, fold_unit(bytes[i] as u16, fold) as u32);
+        i += 1;
+    }
+    hash
+}
+
+pub const fn sdbm_utf16(units: &[u16]) -> u32 {
+    sdbm_wide(units, false)
+}
+
+pub const fn sdbm_utf16_ci(units: &[u16]) -> u32 {
+    sdbm_wide(units, true)
+}
+
+const fn sdbm_wide(units: &[u16], fold: bool) -> u32 {
+    let mut hash = 0u32;
+    let mut i = 0;
+    while i < units.len() {
+        hash = sdbm_step(hash, fold_unit(units[i], fold) as u32);
         i += 1;
     }
     hash
@@ -56,6 +400,74 @@ macro_rules! ct_xxhash {
     }};
 }
 
+#[macro_export]
+macro_rules! ct_xxh32 {
+    ($data:expr) => {{
+        const _H: u32 = $crate::hash::xxh32($data, 0);
+        _H
+    }};
+    ($data:expr, $seed:expr) => {{
+        const _H: u32 = $crate::hash::xxh32($data, $seed);
+        _H
+    }};
+}
+
+#[macro_export]
+macro_rules! ct_xxh64 {
+    ($data:expr) => {{
+        const _H: u64 = $crate::hash::xxh64($data, 0);
+        _H
+    }};
+    ($data:expr, $seed:expr) => {{
+        const _H: u64 = $crate::hash::xxh64($data, $seed);
+        _H
+    }};
+}
+
+#[macro_export]
+macro_rules! ct_fnv1a {
+    ($data:expr) => {{
+        const _H: u32 = $crate::hash::fnv1a32($data);
+        _H
+    }};
+}
+
+#[macro_export]
+macro_rules! ct_fnv1a64 {
+    ($data:expr) => {{
+        const _H: u64 = $crate::hash::fnv1a64($data);
+        _H
+    }};
+}
+
+#[macro_export]
+macro_rules! ct_crc32 {
+    ($data:expr) => {{
+        const _H: u32 = $crate::hash::crc32($data);
+        _H
+    }};
+}
+
+#[macro_export]
+macro_rules! ct_murmur3 {
+    ($data:expr) => {{
+        const _H: u32 = $crate::hash::murmur3_32($data, 0);
+        _H
+    }};
+    ($data:expr, $seed:expr) => {{
+        const _H: u32 = $crate::hash::murmur3_32($data, $seed);
+        _H
+    }};
+}
+
+#[macro_export]
+macro_rules! ct_djb2 {
+    ($data:expr) => {{
+        const _H: u32 = $crate::hash::djb2($data);
+        _H
+    }};
+}
+
 #[macro_export]
 macro_rules! ct_sdbm {
     ($s:expr) => {{
@@ -64,3 +476,68 @@ macro_rules! ct_sdbm {
     }};
 }
 
+
+#[macro_export]
+macro_rules! ct_xxh32_ci {
+    ($data:expr) => {{
+        const _H: u32 = $crate::hash::xxh32_ci($data, 0);
+        _H
+    }};
+    ($data:expr, $seed:expr) => {{
+        const _H: u32 = $crate::hash::xxh32_ci($data, $seed);
+        _H
+    }};
+}
+
+#[macro_export]
+macro_rules! ct_sdbm_ci {
+    ($s:expr) => {{
+        const _H: u32 = $crate::hash::sdbm_hash_ci($s);
+        _H
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __ct_wide_hash {
+    ($f:ident, $s:expr $(, $seed:expr)?) => {{
+        const _LEN: usize = $crate::wide::utf16_len($s);
+        const _UNITS: [u16; _LEN] = $crate::wide::encode_utf16::<_LEN>($s);
+        const _H: u32 = $crate::hash::$f(&_UNITS $(, $seed)?);
+        _H
+    }};
+}
+
+#[macro_export]
+macro_rules! ct_xxh32_utf16 {
+    ($s:expr) => {
+        $crate::__ct_wide_hash!(xxh32_utf16, $s, 0)
+    };
+    ($s:expr, $seed:expr) => {
+        $crate::__ct_wide_hash!(xxh32_utf16, $s, $seed)
+    };
+}
+
+#[macro_export]
+macro_rules! ct_xxh32_utf16_ci {
+    ($s:expr) => {
+        $crate::__ct_wide_hash!(xxh32_utf16_ci, $s, 0)
+    };
+    ($s:expr, $seed:expr) => {
+        $crate::__ct_wide_hash!(xxh32_utf16_ci, $s, $seed)
+    };
+}
+
+#[macro_export]
+macro_rules! ct_sdbm_utf16 {
+    ($s:expr) => {
+        $crate::__ct_wide_hash!(sdbm_utf16, $s)
+    };
+}
+
+#[macro_export]
+macro_rules! ct_sdbm_utf16_ci {
+    ($s:expr) => {
+        $crate::__ct_wide_hash!(sdbm_utf16_ci, $s)
+    };
+}
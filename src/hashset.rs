@@ -0,0 +1,222 @@
+use crate::hash::{sdbm_hash, xxh32};
+use crate::rng::xxhash_mix;
+
+const EMPTY: usize = usize::MAX;
+pub const PERFECT_TRIES: u32 = 1 << 16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HashKind {
+    Xxh32,
+    Sdbm,
+}
+
+impl HashKind {
+    pub const fn hash(self, name: &str) -> u32 {
+        match self {
+            HashKind::Xxh32 => xxh32(name.as_bytes(), 0),
+            HashKind::Sdbm => sdbm_hash(name),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct CtHashSet<const N: usize> {
+    kind: HashKind,
+    hashes: [u32; N],
+    order: [usize; N],
+}
+
+impl<const N: usize> CtHashSet<N> {
+    pub const fn new(kind: HashKind, names: &[&str]) -> Self {
+        if names.len() != N {
+            panic!("ct_hash_set: name count does not match the set size");
+        }
+        let mut hashes = [0u32; N];
+        let mut order = [0usize; N];
+        let mut i = 0;
+        while i < N {
+            let h = kind.hash(names[i]);
+            let mut j = i;
+            while j > 0 && hashes[j - 1] > h {
+                hashes[j] = hashes[j - 1];
+                order[j] = order[j - 1];
+                j -= 1;
+            }
+            if j > 0 && hashes[j - 1] == h {
+                panic!("ct_hash_set: two names hash to the same value");
+            }
+            hashes[j] = h;
+            order[j] = i;
+            i += 1;
+        }
+        Self { kind, hashes, order }
+    }
+
+    pub const fn kind(&self) -> HashKind {
+        self.kind
+    }
+
+    pub const fn len(&self) -> usize {
+        N
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        N == 0
+    }
+
+    pub const fn hashes(&self) -> &[u32; N] {
+        &self.hashes
+    }
+
+    const fn slot(&self, hash: u32) -> Option<usize> {
+        let (mut lo, mut hi) = (0, N);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            let h = self.hashes[mid];
+            if h == hash {
+                return Some(mid);
+            }
+            if h < hash {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        None
+    }
+
+    pub const fn contains(&self, hash: u32) -> bool {
+        self.slot(hash).is_some()
+    }
+
+    pub const fn position(&self, hash: u32) -> Option<usize> {
+        match self.slot(hash) {
+            Some(s) => Some(self.order[s]),
+            None => None,
+        }
+    }
+
+    pub const fn contains_name(&self, name: &str) -> bool {
+        self.contains(self.kind.hash(name))
+    }
+
+    pub const fn position_of(&self, name: &str) -> Option<usize> {
+        self.position(self.kind.hash(name))
+    }
+
+    pub const fn perfect<const M: usize>(self) -> PerfectHashSet<N, M> {
+        PerfectHashSet::new(self)
+    }
+}
+
+pub const fn perfect_size(n: usize) -> usize {
+    let mut m = 1;
+    while m < n * 4 {
+        m <<= 1;
+    }
+    m
+}
+
+const fn perfect_index(hash: u32, seed: u32, m: usize) -> usize {
+    (xxhash_mix(hash as u64 | (seed as u64) << 32) as usize) & (m - 1)
+}
+
+const fn try_seed<const N: usize, const M: usize>(hashes: &[u32; N], seed: u32) -> Option<[usize; M]> {
+    let mut slots = [EMPTY; M];
+    let mut i = 0;
+    while i < N {
+        let idx = perfect_index(hashes[i], seed, M);
+        if slots[idx] != EMPTY {
+            return None;
+        }
+        slots[idx] = i;
+        i += 1;
+    }
+    Some(slots)
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct PerfectHashSet<const N: usize, const M: usize> {
+    set: CtHashSet<N>,
+    seed: u32,
+    slots: [usize; M],
+}
+
+impl<const N: usize, const M: usize> PerfectHashSet<N, M> {
+    pub const fn new(set: CtHashSet<N>) -> Self {
+        if !M.is_power_of_two() || M < N {
+            panic!("ct_perfect_hash_set: table size must be a power of two no smaller than the set");
+        }
+        let mut seed = 0;
+        while seed < PERFECT_TRIES {
+            if let Some(slots) = try_seed::<N, M>(&set.hashes, seed) {
+                return Self { set, seed, slots };
+            }
+            seed += 1;
+        }
+        panic!("ct_perfect_hash_set: no perfect seed found, use a larger table");
+    }
+
+    pub const fn seed(&self) -> u32 {
+        self.seed
+    }
+
+    pub const fn set(&self) -> &CtHashSet<N> {
+        &self.set
+    }
+
+    pub const fn position(&self, hash: u32) -> Option<usize> {
+        let s = self.slots[perfect_index(hash, self.seed, M)];
+        if s != EMPTY && self.set.hashes[s] == hash {
+            Some(self.set.order[s])
+        } else {
+            None
+        }
+    }
+
+    pub const fn contains(&self, hash: u32) -> bool {
+        self.position(hash).is_some()
+    }
+
+    pub const fn position_of(&self, name: &str) -> Option<usize> {
+        self.position(self.set.kind.hash(name))
+    }
+
+    pub const fn contains_name(&self, name: &str) -> bool {
+        self.contains(self.set.kind.hash(name))
+    }
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __hash_kind {
+    (xxhash) => { $crate::hashset::HashKind::Xxh32 };
+    (sdbm) => { $crate::hashset::HashKind::Sdbm };
+}
+
+#[macro_export]
+macro_rules! ct_hash_set {
+    ($kind:ident; $($name:expr),* $(,)?) => {{
+        const _NAMES: &[&str] = &[$($name),*];
+        const _SET: $crate::hashset::CtHashSet<{ _NAMES.len() }> =
+            $crate::hashset::CtHashSet::new($crate::__hash_kind!($kind), _NAMES);
+        _SET
+    }};
+    ($($name:expr),* $(,)?) => {
+        $crate::ct_hash_set!(xxhash; $($name),*)
+    };
+}
+
+#[macro_export]
+macro_rules! ct_perfect_hash_set {
+    ($kind:ident; $($name:expr),* $(,)?) => {{
+        const _NAMES: &[&str] = &[$($name),*];
+        const _N: usize = _NAMES.len();
+        const _SET: $crate::hashset::PerfectHashSet<_N, { $crate::hashset::perfect_size(_N) }> =
+            $crate::hashset::PerfectHashSet::new($crate::hashset::CtHashSet::new($crate::__hash_kind!($kind), _NAMES));
+        _SET
+    }};
+    ($($name:expr),* $(,)?) => {
+        $crate::ct_perfect_hash_set!(xxhash; $($name),*)
+    };
+}
@@ -0,0 +1,252 @@
+use core::ffi::{c_char, c_int, c_ulong, c_void};
+
+use crate::hash::{sdbm_hash, xxh32};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SymbolHash {
+    Xxh32(u32),
+    Sdbm(u32),
+}
+
+impl SymbolHash {
+    fn matches(self, name: &[u8]) -> bool {
+        match self {
+            SymbolHash::Xxh32(h) => xxh32(name, 0) == h,
+            SymbolHash::Sdbm(h) => core::str::from_utf8(name).map(sdbm_hash) == Ok(h),
+        }
+    }
+}
+
+#[repr(C)]
+struct DlPhdrInfo {
+    addr: usize,
+    name: *const c_char,
+    phdr: *const Elf64Phdr,
+    phnum: u16,
+}
+
+#[repr(C)]
+struct Elf64Phdr {
+    p_type: u32,
+    p_flags: u32,
+    p_offset: u64,
+    p_vaddr: u64,
+    p_paddr: u64,
+    p_filesz: u64,
+    p_memsz: u64,
+    p_align: u64,
+}
+
+#[repr(C)]
+struct Elf64Dyn {
+    tag: i64,
+    val: u64,
+}
+
+#[repr(C)]
+struct Elf64Sym {
+    name: u32,
+    info: u8,
+    other: u8,
+    shndx: u16,
+    value: u64,
+    size: u64,
+}
+
+const PT_DYNAMIC: u32 = 2;
+const DT_NULL: i64 = 0;
+const DT_HASH: i64 = 4;
+const DT_STRTAB: i64 = 5;
+const DT_SYMTAB: i64 = 6;
+const DT_STRSZ: i64 = 10;
+const DT_GNU_HASH: i64 = 0x6ffffef5;
+const DT_VERSYM: i64 = 0x6ffffff0;
+const STT_FUNC: u8 = 2;
+const STT_GNU_IFUNC: u8 = 10;
+const VERSYM_HIDDEN: u16 = 0x8000;
+const AT_HWCAP: c_ulong = 16;
+
+type DlCallback = unsafe extern "C" fn(*mut DlPhdrInfo, usize, *mut c_void) -> c_int;
+
+extern "C" {
+    fn dl_iterate_phdr(callback: DlCallback, data: *mut c_void) -> c_int;
+    fn getauxval(kind: c_ulong) -> c_ulong;
+}
+
+struct Search {
+    hash: SymbolHash,
+    found: usize,
+}
+
+struct Tables {
+    base: usize,
+    symtab: *const Elf64Sym,
+    strtab: *const u8,
+    strsz: usize,
+    versym: *const u16,
+    count: usize,
+}
+
+fn reloc(base: usize, ptr: u64) -> usize {
+    let ptr = ptr as usize;
+    if ptr < base { base + ptr } else { ptr }
+}
+
+unsafe fn gnu_hash_count(table: *const u32) -> usize {
+    let nbuckets = *table as usize;
+    let symoffset = *table.add(1) as usize;
+    let bloom_size = *table.add(2) as usize;
+    let buckets = table.add(4 + bloom_size * 2);
+    let chains = buckets.add(nbuckets);
+    let mut last = 0;
+    for i in 0..nbuckets {
+        last = last.max(*buckets.add(i) as usize);
+    }
+    if last < symoffset {
+        return symoffset;
+    }
+    while *chains.add(last - symoffset) & 1 == 0 {
+        last += 1;
+    }
+    last + 1
+}
+
+unsafe fn module_tables(info: &DlPhdrInfo) -> Option<Tables> {
+    let base = info.addr;
+    let phdrs = core::slice::from_raw_parts(info.phdr, info.phnum as usize);
+    let dynamic = phdrs.iter().find(|p| p.p_type == PT_DYNAMIC)?;
+    let mut dyn_ptr = (base + dynamic.p_vaddr as usize) as *const Elf64Dyn;
+
+    let (mut symtab, mut strtab, mut strsz, mut versym) = (0, 0, 0, 0);
+    let (mut hash, mut gnu_hash) = (0, 0);
+    while (*dyn_ptr).tag != DT_NULL {
+        let entry = &*dyn_ptr;
+        match entry.tag {
+            DT_SYMTAB => symtab = reloc(base, entry.val),
+            DT_STRTAB => strtab = reloc(base, entry.val),
+            DT_STRSZ => strsz = entry.val as usize,
+            DT_HASH => hash = reloc(base, entry.val),
+            DT_GNU_HASH => gnu_hash = reloc(base, entry.val),
+            DT_VERSYM => versym = reloc(base, entry.val),
+            _ => {}
+        }
+        dyn_ptr = dyn_ptr.add(1);
+    }
+    if symtab == 0 || strtab == 0 {
+        return None;
+    }
+    let count = if hash != 0 {
+        *(hash as *const u32).add(1) as usize
+    } else if gnu_hash != 0 {
+        gnu_hash_count(gnu_hash as *const u32)
+    } else {
+        return None;
+    };
+    Some(Tables {
+        base,
+        symtab: symtab as *const Elf64Sym,
+        strtab: strtab as *const u8,
+        strsz,
+        versym: versym as *const u16,
+        count,
+    })
+}
+
+unsafe fn symbol_name<'a>(tables: &Tables, offset: usize) -> Option<&'a [u8]> {
+    if offset >= tables.strsz {
+        return None;
+    }
+    let start = tables.strtab.add(offset);
+    let max = tables.strsz - offset;
+    let len = (0..max).find(|&i| *start.add(i) == 0)?;
+    Some(core::slice::from_raw_parts(start, len))
+}
+
+unsafe fn find_in(tables: &Tables, hash: SymbolHash) -> Option<usize> {
+    for i in 1..tables.count {
+        let sym = &*tables.symtab.add(i);
+        let kind = sym.info & 0xf;
+        if sym.shndx == 0 || sym.value == 0 || (kind != STT_FUNC && kind != STT_GNU_IFUNC) {
+            continue;
+        }
+        if !tables.versym.is_null() && *tables.versym.add(i) & VERSYM_HIDDEN != 0 {
+            continue;
+        }
+        match symbol_name(tables, sym.name as usize) {
+            Some(name) if hash.matches(name) => {}
+            _ => continue,
+        }
+        let addr = tables.base + sym.value as usize;
+        if kind == STT_GNU_IFUNC {
+            let resolver: unsafe extern "C" fn(c_ulong) -> usize = core::mem::transmute(addr);
+            return Some(resolver(getauxval(AT_HWCAP)));
+        }
+        return Some(addr);
+    }
+    None
+}
+
+unsafe extern "C" fn visit(info: *mut DlPhdrInfo, _size: usize, data: *mut c_void) -> c_int {
+    let search = &mut *(data as *mut Search);
+    match module_tables(&*info).and_then(|tables| find_in(&tables, search.hash)) {
+        Some(addr) => {
+            search.found = addr;
+            1
+        }
+        None => 0,
+    }
+}
+
+pub fn resolve(hash: SymbolHash) -> Option<*const ()> {
+    let mut search = Search { hash, found: 0 };
+    unsafe { dl_iterate_phdr(visit, &mut search as *mut Search as *mut c_void) };
+    if search.found == 0 { None } else { Some(search.found as *const ()) }
+}
+
+pub fn resolve_xxhash(hash: u32) -> Option<*const ()> {
+    resolve(SymbolHash::Xxh32(hash))
+}
+
+pub fn resolve_sdbm(hash: u32) -> Option<*const ()> {
+    resolve(SymbolHash::Sdbm(hash))
+}
+
+#[macro_export]
+macro_rules! obf_import {
+    () => {};
+    ($vis:vis fn $name:ident($($arg:ident : $ty:ty),+ , ... $(,)?) $(-> $ret:ty)? $(; $($rest:tt)*)?) => {
+        #[allow(clippy::missing_safety_doc)]
+        $vis unsafe fn $name() -> unsafe extern "C" fn($($ty),+ , ...) $(-> $ret)? {
+            static SLOT: ::core::sync::atomic::AtomicUsize = ::core::sync::atomic::AtomicUsize::new(0);
+            const HASH: u32 = $crate::ct_xxh32!(stringify!($name).as_bytes());
+            ::core::mem::transmute($crate::import::cached(&SLOT, HASH))
+        }
+        $($crate::obf_import!($($rest)*);)?
+    };
+    ($vis:vis fn $name:ident($($arg:ident : $ty:ty),* $(,)?) $(-> $ret:ty)? $(; $($rest:tt)*)?) => {
+        #[allow(clippy::missing_safety_doc)]
+        $vis unsafe fn $name($($arg: $ty),*) $(-> $ret)? {
+            static SLOT: ::core::sync::atomic::AtomicUsize = ::core::sync::atomic::AtomicUsize::new(0);
+            const HASH: u32 = $crate::ct_xxh32!(stringify!($name).as_bytes());
+            let f: unsafe extern "C" fn($($ty),*) $(-> $ret)? =
+                ::core::mem::transmute($crate::import::cached(&SLOT, HASH));
+            f($($arg),*)
+        }
+        $($crate::obf_import!($($rest)*);)?
+    };
+}
+
+#[doc(hidden)]
+pub fn cached(slot: &core::sync::atomic::AtomicUsize, hash: u32) -> *const () {
+    use core::sync::atomic::Ordering;
+    let enc = slot.load(Ordering::Acquire);
+    if enc != 0 {
+        return crate::pointer::decode_addr(enc) as *const ();
+    }
+    let addr = match resolve_xxhash(hash) {
+        Some(addr) => addr,
+        None => panic!("unresolved import {:#010x}", hash),
+    };
+    slot.store(crate::pointer::encode_addr(addr as usize), Ordering::Release);
+    addr
+}
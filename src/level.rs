@@ -0,0 +1,63 @@
+const fn parse_level(s: &str) -> u8 {
+    let bytes = s.as_bytes();
+    let mut level = 0u8;
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        if b < b'0' || b > b'9' {
+            panic!("NATIVE_OBF_LEVEL must be a number between 0 and 3");
+        }
+        level = level.saturating_mul(10).saturating_add(b - b'0');
+        i += 1;
+    }
+    if level > MAX_LEVEL { MAX_LEVEL } else { level }
+}
+
+pub const MAX_LEVEL: u8 = 3;
+
+const FEATURE_LEVEL: u8 = if cfg!(feature = "level-3") {
+    3
+} else if cfg!(feature = "level-2") {
+    2
+} else if cfg!(feature = "level-1") {
+    1
+} else if cfg!(feature = "level-0") {
+    0
+} else {
+    2
+};
+
+pub const OBF_LEVEL: u8 = match option_env!("NATIVE_OBF_LEVEL") {
+    _ if cfg!(feature = "passthrough") => 0,
+    Some(s) => parse_level(s),
+    None => FEATURE_LEVEL,
+};
+
+pub const fn junk_blocks(level: u8) -> usize {
+    match level {
+        0 => 0,
+        1 | 2 => 1,
+        _ => 3,
+    }
+}
+
+pub const fn mba_rounds(level: u8) -> u32 {
+    match level {
+        0 => 0,
+        1 => 1,
+        2 => 2,
+        _ => 4,
+    }
+}
+
+pub const fn string_rounds(level: u8) -> usize {
+    match level {
+        0 => 0,
+        1 | 2 => 1,
+        _ => 3,
+    }
+}
+
+pub const fn anti_debug_enabled(level: u8) -> bool {
+    level >= 1
+}
@@ -5,16 +5,40 @@ pub mod flow;
 pub mod encoding;
 pub mod pointer;
 pub mod wide;
+pub mod opaque;
+pub mod level;
+pub mod value;
+pub mod cipher;
+pub mod blob;
+pub mod channel;
+pub mod hashset;
+#[cfg(all(target_os = "linux", target_pointer_width = "64"))]
+pub mod import;
+#[cfg(feature = "passthrough")]
+mod passthrough;
+
+pub use rng::{xxhash_mix, siphash_seed, gen_entropy, GLOBAL_SEED, ct_permutation, Permutation};
+pub use hash::{xxhash32, xxh32, xxh64, siphash24, fnv1a32, fnv1a64, crc32, murmur3_32, djb2, sdbm_hash};
+pub use hash::{xxh32_ci, xxh32_utf16, xxh32_utf16_ci, sdbm_hash_ci, sdbm_utf16, sdbm_utf16_ci};
+pub use value::{ObfuscatedValue, GuardedValue, NoUninit, TamperEvent, set_tamper_hook};
+pub use string::FixedStr;
+pub use cipher::{Cascade, CascadeWriter, CascadeReader};
+pub use channel::{ObfChannel, ChannelError};
+pub use hashset::{CtHashSet, PerfectHashSet, HashKind};
+pub use blob::{BlobEncoder, BlobDecoder, BlobHeader, BlobError, CipherId};
+pub use value::{ObfAtomicU32, ObfAtomicU64, ObfAtomicUsize};
+pub use pointer::{ObfPtr, ObfBox, ObfRc, ObfStatic, ObfStaticGuard, EncryptedStatic, EncryptedGuard};
+pub use rust_native_obf_macros::{obfuscate, Obfuscated};
 
-pub use rng::{xxhash_mix, siphash_seed, gen_entropy, GLOBAL_SEED};
-pub use hash::{xxhash32, sdbm_hash};
+#[macro_export]
+macro_rules! no_obf {
+    ($e:expr) => {
+        $e
+    };
+}
 
 pub fn opaque_true() -> bool {
-    let t = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_nanos();
-    (t | 1) != 0
+    opaque::odd_state()
 }
 
 pub fn opaque_identity<T>(val: T) -> T {
@@ -23,17 +47,20 @@ pub fn opaque_identity<T>(val: T) -> T {
 
 pub fn stack_trash() {
     let mut buf = [0u8; 512];
-    for i in 0..512 {
-        buf[i] = ((i * 73) ^ 0xaa) as u8;
+    for (i, b) in buf.iter_mut().enumerate() {
+        *b = ((i * 73) ^ 0xaa) as u8;
     }
     std::hint::black_box(&buf);
 }
 
+#[cfg(not(feature = "passthrough"))]
 #[macro_export]
 macro_rules! hidden_call {
     ($expr:expr) => {{
-        if $crate::opaque_true() {
-            $crate::stack_trash();
+        if $crate::level::OBF_LEVEL == 0 || $crate::opaque_pred!() {
+            if $crate::level::OBF_LEVEL != 0 {
+                $crate::stack_trash();
+            }
             $expr
         } else {
             unreachable!()
@@ -60,18 +87,55 @@ pub fn fake_compute(input: u64) -> u64 {
     val
 }
 
+#[cfg(not(feature = "passthrough"))]
 #[macro_export]
 macro_rules! obf_if {
-    ($cond:expr, $then:block, $else:block) => {{
-        let c = $cond;
-        $crate::stack_trash();
+    (level = $lvl:expr; branchless $cond:expr, $then:block, $else:block) => {{
+        const _LVL: u8 = $lvl;
+        const _KEY: u32 = $crate::ct_rand!(u32, "select");
+        let idx = if _LVL == 0 {
+            ($cond) as usize
+        } else {
+            let enc = $crate::flow::encode_cond($cond & $crate::opaque_pred!(), _KEY);
+            $crate::stack_trash();
+            $crate::flow::select_index(enc, _KEY)
+        };
+        match ::core::hint::black_box(idx) {
+            1 => $then,
+            _ => $else,
+        }
+    }};
+    (level = $lvl:expr; branchless $cond:expr, $then:block) => {
+        $crate::obf_if!(level = $lvl; branchless $cond, $then, {})
+    };
+    (level = $lvl:expr; $cond:expr, $then:block, $else:block) => {{
+        const _LVL: u8 = $lvl;
+        let c = $cond & (_LVL == 0 || $crate::opaque_pred!());
+        if _LVL != 0 {
+            $crate::stack_trash();
+        }
         if c { $then } else { $else }
     }};
-    ($cond:expr, $then:block) => {{
-        let c = $cond;
-        $crate::stack_trash();
+    (level = $lvl:expr; $cond:expr, $then:block) => {{
+        const _LVL: u8 = $lvl;
+        let c = $cond & (_LVL == 0 || $crate::opaque_pred!());
+        if _LVL != 0 {
+            $crate::stack_trash();
+        }
         if c { $then }
     }};
+    (branchless $cond:expr, $then:block, $else:block) => {
+        $crate::obf_if!(level = $crate::level::OBF_LEVEL; branchless $cond, $then, $else)
+    };
+    (branchless $cond:expr, $then:block) => {
+        $crate::obf_if!(level = $crate::level::OBF_LEVEL; branchless $cond, $then, {})
+    };
+    ($cond:expr, $then:block, $else:block) => {
+        $crate::obf_if!(level = $crate::level::OBF_LEVEL; $cond, $then, $else)
+    };
+    ($cond:expr, $then:block) => {
+        $crate::obf_if!(level = $crate::level::OBF_LEVEL; $cond, $then)
+    };
 }
 
 pub fn xor_stream(data: &mut [u8], key: &[u8]) {
@@ -106,34 +170,6 @@ pub fn cascade_decrypt(data: &[u8], rounds: usize) -> Vec<u8> {
     result
 }
 
-pub struct ObfuscatedValue<T> {
-    data: Vec<u8>,
-    key: u64,
-    _marker: std::marker::PhantomData<T>,
-}
-
-impl<T: Sized> ObfuscatedValue<T> {
-    pub fn new(val: T) -> Self {
-        let bytes = unsafe {
-            std::slice::from_raw_parts(&val as *const T as *const u8, std::mem::size_of::<T>())
-        };
-        let key = noise_loop(100);
-        let data = bytes.iter().enumerate()
-            .map(|(i, &b)| b ^ ((key >> (i % 8 * 8)) as u8))
-            .collect();
-        std::mem::forget(val);
-        Self { data, key, _marker: std::marker::PhantomData }
-    }
-
-    pub fn get(&self) -> T {
-        let mut bytes = vec![0u8; self.data.len()];
-        for (i, &b) in self.data.iter().enumerate() {
-            bytes[i] = b ^ ((self.key >> (i % 8 * 8)) as u8);
-        }
-        unsafe { std::ptr::read(bytes.as_ptr() as *const T) }
-    }
-}
-
 pub fn tamper_check(expected: u32) -> bool {
     let actual = ct_xxhash!(b"tamper_check");
     actual == expected
@@ -155,11 +191,15 @@ pub fn anti_debug() -> bool {
     }
 }
 
+#[cfg(not(feature = "passthrough"))]
 #[macro_export]
 macro_rules! debug_trap {
-    () => {
-        if !$crate::anti_debug() {
+    (level = $lvl:expr) => {
+        if $crate::level::anti_debug_enabled($lvl) && !$crate::anti_debug() {
             panic!("debugger detected");
         }
     };
+    () => {
+        $crate::debug_trap!(level = $crate::level::OBF_LEVEL)
+    };
 }
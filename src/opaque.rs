@@ -0,0 +1,92 @@
+use core::sync::atomic::{AtomicPtr, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use crate::rng::{xxhash_mix, GLOBAL_SEED};
+
+const PAIR_MUL: u32 = 0x2545f491;
+
+const fn pair_word(lo: u32) -> u64 {
+    ((lo.wrapping_mul(PAIR_MUL) as u64) << 32) | lo as u64
+}
+
+static ENTROPY: AtomicU32 = AtomicU32::new(xxhash_mix(GLOBAL_SEED) as u32);
+static ODD: AtomicU64 = AtomicU64::new(xxhash_mix(GLOBAL_SEED ^ 0x6f6464) | 1);
+static PAIR: AtomicU64 = AtomicU64::new(pair_word(GLOBAL_SEED as u32));
+static SLOTS: [AtomicUsize; 4] = [
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+    AtomicUsize::new(0),
+];
+static CURSOR: AtomicPtr<AtomicUsize> = AtomicPtr::new(&SLOTS[0] as *const AtomicUsize as *mut AtomicUsize);
+
+pub const PREDICATES: u8 = 6;
+
+#[inline(always)]
+fn opaque_input() -> u32 {
+    ENTROPY.fetch_add(0x9e3779b9, Ordering::Relaxed)
+}
+
+#[inline(never)]
+pub fn consecutive_product() -> bool {
+    let x = opaque_input();
+    x.wrapping_mul(x.wrapping_add(1)) & 1 == 0
+}
+
+#[inline(never)]
+pub fn square_residue() -> bool {
+    let x = opaque_input();
+    x.wrapping_mul(x) & 3 < 2
+}
+
+#[inline(never)]
+pub fn seven_square() -> bool {
+    let x = opaque_input();
+    let y = x.rotate_left(11) ^ 0x5bd1e995;
+    x.wrapping_mul(x) != y.wrapping_mul(y).wrapping_mul(7).wrapping_sub(1)
+}
+
+#[inline(never)]
+pub fn pointer_alias() -> bool {
+    let p = CURSOR.load(Ordering::Relaxed) as usize;
+    let base = SLOTS.as_ptr() as usize;
+    let idx = p.wrapping_sub(base) / core::mem::size_of::<AtomicUsize>();
+    let ok = p >= base && idx < SLOTS.len();
+    let next = &SLOTS[(opaque_input() as usize) % SLOTS.len()];
+    next.fetch_add(1, Ordering::Relaxed);
+    CURSOR.store(next as *const AtomicUsize as *mut AtomicUsize, Ordering::Relaxed);
+    ok
+}
+
+#[inline(never)]
+pub fn odd_state() -> bool {
+    let step = (opaque_input() as u64) << 1;
+    ODD.fetch_add(step, Ordering::Relaxed) & 1 == 1
+}
+
+#[inline(never)]
+pub fn pair_state() -> bool {
+    let w = PAIR.load(Ordering::Relaxed);
+    PAIR.store(pair_word((w as u32).wrapping_add(opaque_input())), Ordering::Relaxed);
+    (w >> 32) as u32 == (w as u32).wrapping_mul(PAIR_MUL)
+}
+
+#[inline(always)]
+pub fn opaque_pick<const ID: u8>() -> bool {
+    match ID % PREDICATES {
+        0 => consecutive_product(),
+        1 => square_residue(),
+        2 => seven_square(),
+        3 => pointer_alias(),
+        4 => odd_state(),
+        _ => pair_state(),
+    }
+}
+
+#[macro_export]
+macro_rules! opaque_pred {
+    () => {
+        $crate::opaque::opaque_pick::<{ $crate::ct_rand!(u8, "opaque") }>()
+    };
+    ($seed:expr) => {
+        $crate::opaque::opaque_pick::<{ $crate::ct_rand!(u8, "opaque", $seed) }>()
+    };
+}
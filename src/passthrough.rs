@@ -0,0 +1,136 @@
+#[macro_export]
+macro_rules! obf_str {
+    (level = $lvl:expr; $s:expr) => {
+        $crate::obf_str!($s)
+    };
+    ($s:expr) => {{
+        const _SRC: &[u8] = $s.as_bytes();
+        const _LEN: usize = _SRC.len();
+        &*$crate::string::DecryptedStr::new($crate::string::copy_bytes::<_LEN>(_SRC))
+    }};
+}
+
+#[macro_export]
+macro_rules! obf_const {
+    (level = $lvl:expr; $val:expr, $ty:ident) => {
+        $crate::obf_const!($val, $ty)
+    };
+    (level = $lvl:expr; $val:literal) => {
+        $crate::obf_const!($val)
+    };
+    ($val:expr, $ty:ident) => {{
+        const _VAL: $ty = $val as $ty;
+        _VAL
+    }};
+    ($val:literal) => {
+        $val
+    };
+}
+
+#[macro_export]
+macro_rules! obf_if {
+    (level = $lvl:expr; $($rest:tt)*) => {
+        $crate::obf_if!($($rest)*)
+    };
+    (branchless $cond:expr, $then:block, $else:block) => {
+        if $cond { $then } else { $else }
+    };
+    (branchless $cond:expr, $then:block) => {
+        if $cond { $then }
+    };
+    ($cond:expr, $then:block, $else:block) => {
+        if $cond { $then } else { $else }
+    };
+    ($cond:expr, $then:block) => {
+        if $cond { $then }
+    };
+}
+
+#[macro_export]
+macro_rules! hidden_call {
+    ($expr:expr) => {
+        $expr
+    };
+}
+
+#[macro_export]
+macro_rules! obf_block {
+    (level = $lvl:expr; $code:block) => {
+        $code
+    };
+    ($code:block) => {
+        $code
+    };
+}
+
+#[macro_export]
+macro_rules! obf_static_ref {
+    ($e:expr) => {{
+        let r: &'static _ = $e;
+        r
+    }};
+}
+
+#[macro_export]
+macro_rules! debug_trap {
+    (level = $lvl:expr) => {
+        ()
+    };
+    () => {
+        ()
+    };
+}
+
+#[macro_export]
+macro_rules! obf_bytes {
+    (level = $lvl:expr; $s:expr) => {
+        $crate::obf_bytes!($s)
+    };
+    ($s:expr) => {{
+        const _SRC: &[u8] = $s;
+        const _LEN: usize = _SRC.len();
+        $crate::string::copy_bytes::<_LEN>(_SRC)
+    }};
+}
+
+#[macro_export]
+macro_rules! obf_wide {
+    (level = $lvl:expr; $s:expr) => {
+        $crate::obf_wide!($s)
+    };
+    ($s:expr) => {{
+        const _LEN: usize = $crate::wide::utf16_len($s);
+        $crate::wide::encode_utf16::<_LEN>($s)
+    }};
+}
+
+#[macro_export]
+macro_rules! obf_bogus {
+    ($code:block) => {
+        $code
+    };
+    ([$($name:ident : $ty:ident = $val:expr),* $(,)?] $code:block) => {{
+        $(const $name: $ty = { let _ = $crate::__obf_mutate!($ty, $val, 0u64); $val };)*
+        $code
+    }};
+}
+
+#[macro_export]
+macro_rules! obf_flatten {
+    (in loop; $($body:tt)*) => {{
+        $($body)*
+    }};
+    ($($body:tt)*) => {{
+        $($body)*
+    }};
+}
+
+#[macro_export]
+macro_rules! obf_call {
+    (unsafe $($f:ident)::+ ( $($arg:expr),* $(,)? )) => {
+        $($f)::+($($arg),*)
+    };
+    ($($f:ident)::+ ( $($arg:expr),* $(,)? )) => {
+        $($f)::+($($arg),*)
+    };
+}
@@ -1,4 +1,15 @@
+use core::fmt;
+use core::cell::UnsafeCell;
 use core::hint::black_box;
+use core::marker::PhantomData;
+use core::mem::MaybeUninit;
+use core::ops::{Deref, DerefMut};
+use core::sync::atomic::{AtomicUsize, Ordering};
+use std::rc::Rc;
+use std::sync::{Mutex, MutexGuard, TryLockError};
+
+use crate::string::{decrypt_bytes, encrypt_bytes};
+use crate::value::NoUninit;
 
 const fn ptr_mangle<const SEED: u64>(offset: u32) -> usize {
     let mut val = offset as u64;
@@ -24,6 +35,7 @@ pub fn obf_ref<T: ?Sized, const OFF: u32, const SEED: u64>(r: &'static T) -> &'s
     }
 }
 
+#[deprecated(note = "hands out aliasing `&'static mut`, use `ObfStatic`")]
 pub fn obf_ref_mut<T: ?Sized, const OFF: u32, const SEED: u64>(r: &'static mut T) -> &'static mut T {
     unsafe {
         let mut ptr: *mut T = r;
@@ -34,6 +46,7 @@ pub fn obf_ref_mut<T: ?Sized, const OFF: u32, const SEED: u64>(r: &'static mut T
     }
 }
 
+#[cfg(not(feature = "passthrough"))]
 #[macro_export]
 macro_rules! obf_static_ref {
     ($e:expr) => {
@@ -43,12 +56,535 @@ macro_rules! obf_static_ref {
     };
 }
 
+#[deprecated(note = "`obf_static_mut!` hands out aliasing `&'static mut`, use `ObfStatic`")]
 #[macro_export]
 macro_rules! obf_static_mut {
-    ($e:expr) => {
-        $crate::pointer::obf_ref_mut::<_,
+    ($e:expr) => {{
+        #[allow(deprecated)]
+        let restore = $crate::pointer::obf_ref_mut::<_,
             { $crate::ct_rand!(u32, stringify!($e)) },
-            { $crate::ct_rand!(u64, stringify!($e), "ptr") }>($e)
+            { $crate::ct_rand!(u64, stringify!($e), "ptr") }>;
+        restore($e)
+    }};
+}
+
+
+#[inline(always)]
+fn process_key() -> usize {
+    crate::rng::runtime_seed() as usize
+}
+
+#[inline(always)]
+fn key_rotation(key: usize) -> u32 {
+    (key >> 3) as u32 % (usize::BITS - 1) + 1
+}
+
+#[inline(never)]
+pub(crate) fn encode_addr(addr: usize) -> usize {
+    let key = process_key();
+    (black_box(addr) ^ key).rotate_left(key_rotation(key))
+}
+
+#[inline(never)]
+pub(crate) fn decode_addr(enc: usize) -> usize {
+    let key = process_key();
+    black_box(enc).rotate_right(key_rotation(key)) ^ key
+}
+
+pub struct ObfPtr<T> {
+    enc: usize,
+    _marker: PhantomData<*mut T>,
+}
+
+impl<T> ObfPtr<T> {
+    pub fn new(ptr: *mut T) -> Self {
+        Self { enc: encode_addr(ptr as usize), _marker: PhantomData }
+    }
+
+    pub fn null() -> Self {
+        Self::new(core::ptr::null_mut())
+    }
+
+    pub fn get(&self) -> *mut T {
+        decode_addr(self.enc) as *mut T
+    }
+
+    pub fn encoded(&self) -> usize {
+        self.enc
+    }
+
+    pub fn set(&mut self, ptr: *mut T) {
+        self.enc = encode_addr(ptr as usize);
+    }
+
+    pub fn is_null(&self) -> bool {
+        self.get().is_null()
+    }
+
+    /// # Safety
+    /// Same requirements as `<*const T>::as_ref` on the decoded pointer.
+    pub unsafe fn as_ref<'a>(&self) -> Option<&'a T> {
+        self.get().as_ref()
+    }
+
+    /// # Safety
+    /// Same requirements as `<*mut T>::as_mut` on the decoded pointer.
+    pub unsafe fn as_mut<'a>(&self) -> Option<&'a mut T> {
+        self.get().as_mut()
+    }
+}
+
+impl<T> Clone for ObfPtr<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ObfPtr<T> {}
+
+impl<T> PartialEq for ObfPtr<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.enc == other.enc
+    }
+}
+
+impl<T> Eq for ObfPtr<T> {}
+
+impl<T> From<*mut T> for ObfPtr<T> {
+    fn from(ptr: *mut T) -> Self {
+        Self::new(ptr)
+    }
+}
+
+impl<T> fmt::Debug for ObfPtr<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ObfPtr({:#x})", self.enc)
+    }
+}
+
+pub struct ObfBox<T> {
+    ptr: ObfPtr<T>,
+    _marker: PhantomData<Box<T>>,
+}
+
+unsafe impl<T: Send> Send for ObfBox<T> {}
+unsafe impl<T: Sync> Sync for ObfBox<T> {}
+
+impl<T> ObfBox<T> {
+    pub fn new(val: T) -> Self {
+        Self::from_box(Box::new(val))
+    }
+
+    pub fn from_box(b: Box<T>) -> Self {
+        Self { ptr: ObfPtr::new(Box::into_raw(b)), _marker: PhantomData }
+    }
+
+    pub fn into_box(self) -> Box<T> {
+        let raw = self.ptr.get();
+        core::mem::forget(self);
+        unsafe { Box::from_raw(raw) }
+    }
+
+    pub fn into_inner(self) -> T {
+        *self.into_box()
+    }
+}
+
+impl<T> Deref for ObfBox<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.ptr.get() }
+    }
+}
+
+impl<T> DerefMut for ObfBox<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.ptr.get() }
+    }
+}
+
+impl<T> Drop for ObfBox<T> {
+    fn drop(&mut self) {
+        unsafe { drop(Box::from_raw(self.ptr.get())) }
+    }
+}
+
+impl<T: Clone> Clone for ObfBox<T> {
+    fn clone(&self) -> Self {
+        Self::new((**self).clone())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ObfBox<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T> From<Box<T>> for ObfBox<T> {
+    fn from(b: Box<T>) -> Self {
+        Self::from_box(b)
+    }
+}
+
+pub struct ObfRc<T> {
+    ptr: ObfPtr<T>,
+    _marker: PhantomData<Rc<T>>,
+}
+
+impl<T> ObfRc<T> {
+    pub fn new(val: T) -> Self {
+        Self::from_rc(Rc::new(val))
+    }
+
+    pub fn from_rc(rc: Rc<T>) -> Self {
+        Self { ptr: ObfPtr::new(Rc::into_raw(rc) as *mut T), _marker: PhantomData }
+    }
+
+    pub fn into_rc(self) -> Rc<T> {
+        let raw = self.ptr.get();
+        core::mem::forget(self);
+        unsafe { Rc::from_raw(raw) }
+    }
+
+    pub fn strong_count(this: &Self) -> usize {
+        let rc = core::mem::ManuallyDrop::new(unsafe { Rc::from_raw(this.ptr.get()) });
+        Rc::strong_count(&rc)
+    }
+
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        this.ptr == other.ptr
+    }
+}
+
+impl<T> Deref for ObfRc<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.ptr.get() }
+    }
+}
+
+impl<T> Clone for ObfRc<T> {
+    fn clone(&self) -> Self {
+        unsafe { Rc::increment_strong_count(self.ptr.get()) };
+        Self { ptr: self.ptr, _marker: PhantomData }
+    }
+}
+
+impl<T> Drop for ObfRc<T> {
+    fn drop(&mut self) {
+        unsafe { drop(Rc::from_raw(self.ptr.get())) }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ObfRc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T> From<Rc<T>> for ObfRc<T> {
+    fn from(rc: Rc<T>) -> Self {
+        Self::from_rc(rc)
+    }
+}
+
+pub struct ObfStatic<T> {
+    lock: Mutex<()>,
+    value: UnsafeCell<T>,
+}
+
+unsafe impl<T: Send> Sync for ObfStatic<T> {}
+
+impl<T> ObfStatic<T> {
+    pub const fn new(value: T) -> Self {
+        Self { lock: Mutex::new(()), value: UnsafeCell::new(value) }
+    }
+
+    pub fn lock(&self) -> ObfStaticGuard<'_, T> {
+        let held = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        ObfStaticGuard { ptr: ObfPtr::new(self.value.get()), _held: held }
+    }
+
+    pub fn try_lock(&self) -> Option<ObfStaticGuard<'_, T>> {
+        let held = match self.lock.try_lock() {
+            Ok(held) => held,
+            Err(TryLockError::Poisoned(e)) => e.into_inner(),
+            Err(TryLockError::WouldBlock) => return None,
+        };
+        Some(ObfStaticGuard { ptr: ObfPtr::new(self.value.get()), _held: held })
+    }
+
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        f(&self.lock())
+    }
+
+    pub fn with_mut<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        f(&mut self.lock())
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+}
+
+impl<T> fmt::Debug for ObfStatic<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ObfStatic(<redacted>)")
+    }
+}
+
+pub struct ObfStaticGuard<'a, T> {
+    ptr: ObfPtr<T>,
+    _held: MutexGuard<'a, ()>,
+}
+
+impl<T> Deref for ObfStaticGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.ptr.get() }
+    }
+}
+
+impl<T> DerefMut for ObfStaticGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.ptr.get() }
+    }
+}
+
+pub struct EncryptedStatic<T: NoUninit, const N: usize> {
+    lock: Mutex<()>,
+    data: UnsafeCell<[u8; N]>,
+    keys: &'static [u8; N],
+    _marker: PhantomData<T>,
+}
+
+unsafe impl<T: NoUninit + Send, const N: usize> Sync for EncryptedStatic<T, N> {}
+
+impl<T: NoUninit, const N: usize> EncryptedStatic<T, N> {
+    /// # Safety
+    /// `data` must be the bytes of a valid `T` encrypted under `keys`, as `obf_static!` produces them.
+    #[doc(hidden)]
+    pub const unsafe fn new(data: [u8; N], keys: &'static [u8; N]) -> Self {
+        assert!(core::mem::size_of::<T>() == N);
+        Self { lock: Mutex::new(()), data: UnsafeCell::new(data), keys, _marker: PhantomData }
+    }
+
+    pub fn lock(&self) -> EncryptedGuard<'_, T, N> {
+        let held = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let mut plain = decrypt_bytes(unsafe { &*self.data.get() }, self.keys);
+        let value = unsafe { core::mem::transmute_copy::<[u8; N], MaybeUninit<T>>(&plain) };
+        wipe(&mut plain);
+        EncryptedGuard { owner: self, value, dirty: false, _held: held }
+    }
+
+    pub fn get(&self) -> T {
+        *self.lock()
+    }
+
+    pub fn set(&self, value: T) {
+        *self.lock() = value;
+    }
+
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        f(&self.lock())
+    }
+
+    pub fn with_mut<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        f(&mut self.lock())
+    }
+}
+
+impl<T: NoUninit, const N: usize> fmt::Debug for EncryptedStatic<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EncryptedStatic(<redacted>)")
+    }
+}
+
+fn wipe<V>(value: &mut V) {
+    let ptr = value as *mut V as *mut u8;
+    for i in 0..core::mem::size_of::<V>() {
+        unsafe { core::ptr::write_volatile(ptr.add(i), 0) };
+    }
+}
+
+pub struct EncryptedGuard<'a, T: NoUninit, const N: usize> {
+    owner: &'a EncryptedStatic<T, N>,
+    value: MaybeUninit<T>,
+    dirty: bool,
+    _held: MutexGuard<'a, ()>,
+}
+
+impl<T: NoUninit, const N: usize> Deref for EncryptedGuard<'_, T, N> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { self.value.assume_init_ref() }
+    }
+}
+
+impl<T: NoUninit, const N: usize> DerefMut for EncryptedGuard<'_, T, N> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.dirty = true;
+        unsafe { self.value.assume_init_mut() }
+    }
+}
+
+impl<T: NoUninit, const N: usize> Drop for EncryptedGuard<'_, T, N> {
+    fn drop(&mut self) {
+        if self.dirty {
+            let mut plain = unsafe { core::mem::transmute_copy::<MaybeUninit<T>, [u8; N]>(&self.value) };
+            let enc = encrypt_bytes::<N>(&plain, self.owner.keys);
+            unsafe { core::ptr::write_volatile(self.owner.data.get(), enc) };
+            wipe(&mut plain);
+        }
+        wipe(&mut self.value);
+    }
+}
+
+#[macro_export]
+macro_rules! obf_static {
+    ($(#[$attr:meta])* $vis:vis static $name:ident : $ty:ty = $init:expr;) => {
+        $(#[$attr])*
+        $vis static $name: $crate::pointer::EncryptedStatic<$ty, { ::core::mem::size_of::<$ty>() }> = {
+            const _LEN: usize = ::core::mem::size_of::<$ty>();
+            const _INIT: $ty = $init;
+            const _RAW: [u8; _LEN] = unsafe { ::core::mem::transmute::<$ty, [u8; _LEN]>(_INIT) };
+            const _KEYS: [u8; _LEN] = $crate::string::gen_keystream::<_LEN>(
+                $crate::ct_rand!(u32, stringify!($name), "static"));
+            unsafe {
+                $crate::pointer::EncryptedStatic::new($crate::string::encrypt_bytes::<_LEN>(&_RAW, &_KEYS), &_KEYS)
+            }
+        };
     };
 }
 
+pub trait FnPtr: Copy {
+    fn to_addr(self) -> usize;
+
+    /// # Safety
+    /// `addr` must come from `to_addr` on the same function pointer type.
+    unsafe fn from_addr(addr: usize) -> Self;
+}
+
+macro_rules! impl_fn_ptr {
+    ($($arg:ident),*) => {
+        impl_fn_ptr!(@one fn($($arg),*) -> R; $($arg),*);
+        impl_fn_ptr!(@one unsafe fn($($arg),*) -> R; $($arg),*);
+        impl_fn_ptr!(@one extern "C" fn($($arg),*) -> R; $($arg),*);
+        impl_fn_ptr!(@one unsafe extern "C" fn($($arg),*) -> R; $($arg),*);
+    };
+    (@one $ty:ty; $($arg:ident),*) => {
+        impl<R, $($arg),*> FnPtr for $ty {
+            #[inline(always)]
+            fn to_addr(self) -> usize {
+                self as usize
+            }
+
+            #[inline(always)]
+            unsafe fn from_addr(addr: usize) -> Self {
+                core::mem::transmute_copy(&addr)
+            }
+        }
+    };
+}
+
+impl_fn_ptr!();
+impl_fn_ptr!(A);
+impl_fn_ptr!(A, B);
+impl_fn_ptr!(A, B, C);
+impl_fn_ptr!(A, B, C, D);
+impl_fn_ptr!(A, B, C, D, E);
+impl_fn_ptr!(A, B, C, D, E, G);
+impl_fn_ptr!(A, B, C, D, E, G, H);
+impl_fn_ptr!(A, B, C, D, E, G, H, I);
+
+const fn fn_key<const SEED: u64>() -> usize {
+    crate::rng::xxhash_mix(SEED ^ 0x66756e63) as usize
+}
+
+#[inline(always)]
+pub fn encode_fn<const SEED: u64>(addr: usize) -> usize {
+    (black_box(addr) ^ fn_key::<SEED>()).rotate_left((SEED % 61) as u32 + 1)
+}
+
+pub struct ObfFn<F: FnPtr, const SEED: u64> {
+    enc: usize,
+    _marker: core::marker::PhantomData<F>,
+}
+
+impl<F: FnPtr, const SEED: u64> ObfFn<F, SEED> {
+    #[inline(never)]
+    pub fn new(f: F) -> Self {
+        Self { enc: encode_fn::<SEED>(f.to_addr()), _marker: core::marker::PhantomData }
+    }
+
+    /// # Safety
+    /// `slot` must start at zero and only ever be written by `cached` with the same `F` and `SEED`.
+    #[doc(hidden)]
+    #[inline(never)]
+    pub unsafe fn cached(slot: &AtomicUsize, init: impl FnOnce() -> F) -> Self {
+        let mut enc = slot.load(Ordering::Acquire);
+        if enc == 0 {
+            enc = encode_fn::<SEED>(black_box(init)().to_addr());
+            slot.store(enc, Ordering::Release);
+        }
+        Self { enc, _marker: core::marker::PhantomData }
+    }
+
+    pub fn encoded(&self) -> usize {
+        self.enc
+    }
+
+    #[inline(never)]
+    pub fn get(&self) -> F {
+        let raw = black_box(self.enc).rotate_right((SEED % 61) as u32 + 1) ^ fn_key::<SEED>();
+        unsafe { F::from_addr(raw) }
+    }
+}
+
+impl<F: FnPtr, const SEED: u64> Clone for ObfFn<F, SEED> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<F: FnPtr, const SEED: u64> Copy for ObfFn<F, SEED> {}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __obf_infer {
+    ($t:tt) => { _ };
+}
+
+#[cfg(not(feature = "passthrough"))]
+#[macro_export]
+macro_rules! obf_call {
+    (unsafe $($f:ident)::+ ( $($arg:expr),* $(,)? )) => {{
+        $crate::__obf_call!([unsafe fn] $($f)::+; $($arg),*)
+    }};
+    ($($f:ident)::+ ( $($arg:expr),* $(,)? )) => {{
+        $crate::__obf_call!([fn] $($f)::+; $($arg),*)
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __obf_call {
+    ([$($kind:tt)+] $($f:ident)::+; $($arg:expr),*) => {{
+        const _SEED: u64 = $crate::ct_rand!(u64, stringify!($($f)::+), "call");
+        static _SLOT: ::core::sync::atomic::AtomicUsize = ::core::sync::atomic::AtomicUsize::new(0);
+        let f = unsafe {
+            $crate::pointer::ObfFn::<$($kind)+($($crate::__obf_infer!($arg)),*) -> _, _SEED>::cached(
+                &_SLOT, || $($f)::+)
+        }.get();
+        f($($arg),*)
+    }};
+}
@@ -1,3 +1,7 @@
+use core::sync::atomic::{AtomicU64, Ordering};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
 pub const fn xxhash_mix(mut x: u64) -> u64 {
     x ^= x >> 33;
     x = x.wrapping_mul(0xc2b2ae3d27d4eb4f);
@@ -29,10 +33,172 @@ pub const GLOBAL_SEED: u64 = match option_env!("NATIVE_OBF_SEED") {
     None => xxhash_mix(0x1234567890abcdef),
 };
 
+static RUNTIME_SEED: AtomicU64 = AtomicU64::new(0);
+static RUNTIME_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+#[inline(never)]
+pub fn runtime_seed() -> u64 {
+    let seed = RUNTIME_SEED.load(Ordering::Relaxed);
+    if seed != 0 {
+        return seed;
+    }
+    let fresh = xxhash_mix(RandomState::new().build_hasher().finish() ^ GLOBAL_SEED) | 1;
+    match RUNTIME_SEED.compare_exchange(0, fresh, Ordering::Relaxed, Ordering::Relaxed) {
+        Ok(_) => fresh,
+        Err(existing) => existing,
+    }
+}
+
+#[inline(never)]
+pub fn runtime_key() -> u64 {
+    let n = RUNTIME_COUNTER.fetch_add(0x9e3779b97f4a7c15, Ordering::Relaxed);
+    xxhash_mix(runtime_seed() ^ n) | 1
+}
+
+const GOLDEN: u64 = 0x9e3779b97f4a7c15;
+
+pub const fn rand_u128(v: u64) -> u128 {
+    (xxhash_mix(v ^ GOLDEN) as u128) << 64 | v as u128
+}
+
+pub const fn rand_f32(v: u64) -> f32 {
+    (v >> 40) as f32 / (1u32 << 24) as f32
+}
+
+pub const fn rand_f64(v: u64) -> f64 {
+    (v >> 11) as f64 / (1u64 << 53) as f64
+}
+
+pub const fn rand_char(v: u64) -> char {
+    let n = rand_below(v, 0x110000 - 0x800) as u32;
+    let n = if n >= 0xd800 { n + 0x800 } else { n };
+    match char::from_u32(n) {
+        Some(c) => c,
+        None => panic!("rand_char produced a surrogate"),
+    }
+}
+
+pub const fn rand_nth(v: u64, n: u64) -> u64 {
+    xxhash_mix(v ^ n.wrapping_mul(GOLDEN))
+}
+
+pub const fn rand_below(v: u64, bound: u128) -> u128 {
+    if bound == 0 {
+        panic!("rand_below: empty range");
+    }
+    let mask = if bound.is_power_of_two() { bound - 1 } else { bound.next_power_of_two() - 1 };
+    let mut n = 0;
+    loop {
+        let x = rand_u128(rand_nth(v, n)) & mask;
+        if x < bound {
+            return x;
+        }
+        n += 1;
+    }
+}
+
+pub const fn rand_range(v: u64, lo: i128, hi: i128) -> i128 {
+    if lo >= hi {
+        panic!("ct_rand_range: empty range");
+    }
+    lo.wrapping_add(rand_below(v, hi.abs_diff(lo)) as i128)
+}
+
+#[doc(hidden)]
+pub trait RangeInt: Copy {
+    const MIN: i128;
+    const MAX: i128;
+}
+
+macro_rules! range_int {
+    ($($ty:ident),*) => {
+        $(impl RangeInt for $ty {
+            const MIN: i128 = $ty::MIN as i128;
+            const MAX: i128 = if $ty::MAX as u128 > i128::MAX as u128 { i128::MAX } else { $ty::MAX as i128 };
+        })*
+    };
+}
+
+range_int!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+#[doc(hidden)]
+pub const fn range_fits<T: RangeInt>(_: &T, range: &core::ops::Range<i128>) -> bool {
+    range.start >= T::MIN && range.end - 1 <= T::MAX
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Permutation<const N: usize> {
+    pub forward: [usize; N],
+    pub inverse: [usize; N],
+}
+
+impl<const N: usize> Permutation<N> {
+    pub const fn get(&self, i: usize) -> usize {
+        self.forward[i]
+    }
+
+    pub const fn position(&self, v: usize) -> usize {
+        self.inverse[v]
+    }
+
+    pub const fn apply<T: Copy>(&self, items: [T; N]) -> [T; N] {
+        let mut out = items;
+        let mut i = 0;
+        while i < N {
+            out[i] = items[self.forward[i]];
+            i += 1;
+        }
+        out
+    }
+
+    pub const fn unapply<T: Copy>(&self, items: [T; N]) -> [T; N] {
+        let mut out = items;
+        let mut i = 0;
+        while i < N {
+            out[i] = items[self.inverse[i]];
+            i += 1;
+        }
+        out
+    }
+}
+
+pub const fn ct_permutation<const N: usize>(seed: u64) -> Permutation<N> {
+    let mut forward = [0usize; N];
+    let mut i = 0;
+    while i < N {
+        forward[i] = i;
+        i += 1;
+    }
+    let mut i = N;
+    while i > 1 {
+        i -= 1;
+        let j = rand_below(rand_nth(seed, i as u64), i as u128 + 1) as usize;
+        let tmp = forward[i];
+        forward[i] = forward[j];
+        forward[j] = tmp;
+    }
+    let mut inverse = [0usize; N];
+    let mut i = 0;
+    while i < N {
+        inverse[forward[i]] = i;
+        i += 1;
+    }
+    Permutation { forward, inverse }
+}
+
+#[macro_export]
+macro_rules! ct_shuffle {
+    ($n:expr $(, $seed:expr)*) => {{
+        const _PERM: $crate::rng::Permutation<{ $n }> = $crate::rng::ct_permutation::<{ $n }>(
+            $crate::rng::gen_entropy(concat!(file!(), ":", line!(), ":", column!() $(, ":", $seed)*)));
+        _PERM
+    }};
+}
+
 #[macro_export]
 macro_rules! ct_rand {
     ($ty:ident $(, $seed:expr)*) => {{
-        const _RND: $ty = $crate::rng::cast_rand!($ty, 
+        const _RND: $ty = $crate::cast_rand!($ty, 
             $crate::rng::gen_entropy(concat!(file!(), ":", line!(), ":", column!() $(, ":", $seed)*)));
         _RND
     }};
@@ -51,5 +217,61 @@ macro_rules! cast_rand {
     (i64, $v:expr) => { $v as i64 };
     (isize, $v:expr) => { $v as isize };
     (bool, $v:expr) => { ($v & 1) == 1 };
+    (u128, $v:expr) => { $crate::rng::rand_u128($v) };
+    (i128, $v:expr) => { $crate::rng::rand_u128($v) as i128 };
+    (f32, $v:expr) => { $crate::rng::rand_f32($v) };
+    (f64, $v:expr) => { $crate::rng::rand_f64($v) };
+    (char, $v:expr) => { $crate::rng::rand_char($v) };
+}
+
+#[macro_export]
+macro_rules! ct_rand_array {
+    ($ty:ident, $n:expr $(, $seed:expr)*) => {{
+        const _ARR: [$ty; $n] = {
+            let base = $crate::rng::gen_entropy(concat!(file!(), ":", line!(), ":", column!() $(, ":", $seed)*));
+            let mut arr = [$crate::cast_rand!($ty, 0u64); $n];
+            let mut i = 0;
+            while i < $n {
+                arr[i] = $crate::cast_rand!($ty, $crate::rng::rand_nth(base, i as u64));
+                i += 1;
+            }
+            arr
+        };
+        _ARR
+    }};
+}
+
+#[macro_export]
+macro_rules! ct_rand_range {
+    ($ty:ident, $range:expr $(, $seed:expr)*) => {{
+        const _RND: $ty = {
+            let range: ::core::ops::Range<i128> = $range;
+            let v = $crate::rng::rand_range(
+                $crate::rng::gen_entropy(concat!(file!(), ":", line!(), ":", column!() $(, ":", $seed)*)),
+                range.start,
+                range.end,
+            );
+            let v = v as $ty;
+            if !$crate::rng::range_fits(&v, &range) {
+                panic!("ct_rand_range: range does not fit the target type");
+            }
+            v
+        };
+        _RND
+    }};
+    ($range:expr $(, $seed:expr)*) => {
+        const {
+            let range: ::core::ops::Range<i128> = $range;
+            let v = $crate::rng::rand_range(
+                $crate::rng::gen_entropy(concat!(file!(), ":", line!(), ":", column!() $(, ":", $seed)*)),
+                range.start,
+                range.end,
+            ) as _;
+            if !$crate::rng::range_fits(&v, &range) {
+                panic!("ct_rand_range: range does not fit the target type");
+            }
+            v
+        }
+    };
 }
 
@@ -1,4 +1,4 @@
-use core::ptr::{read_volatile, write};
+use core::ptr::{read_volatile, write, write_unaligned};
 
 const fn pcg_step(mut state: u32) -> u32 {
     state = state.wrapping_mul(747796405).wrapping_add(2891336453);
@@ -33,6 +33,16 @@ pub const fn encrypt_bytes<const N: usize>(data: &[u8], keys: &[u8; N]) -> [u8;
     result
 }
 
+pub const fn copy_bytes<const N: usize>(data: &[u8]) -> [u8; N] {
+    let mut result = [0u8; N];
+    let mut i = 0;
+    while i < N {
+        result[i] = data[i];
+        i += 1;
+    }
+    result
+}
+
 pub fn decrypt_bytes<const N: usize>(data: &[u8; N], keys: &[u8; N]) -> [u8; N] {
     let mut result = [0u8; N];
     let mut i = 0;
@@ -41,18 +51,18 @@ pub fn decrypt_bytes<const N: usize>(data: &[u8; N], keys: &[u8; N]) -> [u8; N]
         let dst = result.as_mut_ptr();
         #[cfg(target_pointer_width = "64")]
         while i + 8 <= N {
-            let enc = read_volatile(src.add(i) as *const u64);
+            let enc = u64::from_ne_bytes(read_volatile(src.add(i) as *const [u8; 8]));
             let key = u64::from_ne_bytes([
                 keys[i], keys[i+1], keys[i+2], keys[i+3],
                 keys[i+4], keys[i+5], keys[i+6], keys[i+7],
             ]);
-            write(dst.add(i) as *mut u64, enc ^ key);
+            write_unaligned(dst.add(i) as *mut u64, enc ^ key);
             i += 8;
         }
         while i + 4 <= N {
-            let enc = read_volatile(src.add(i) as *const u32);
+            let enc = u32::from_ne_bytes(read_volatile(src.add(i) as *const [u8; 4]));
             let key = u32::from_ne_bytes([keys[i], keys[i+1], keys[i+2], keys[i+3]]);
-            write(dst.add(i) as *mut u32, enc ^ key);
+            write_unaligned(dst.add(i) as *mut u32, enc ^ key);
             i += 4;
         }
         while i < N {
@@ -64,6 +74,56 @@ pub fn decrypt_bytes<const N: usize>(data: &[u8; N], keys: &[u8; N]) -> [u8; N]
     result
 }
 
+pub const fn gen_round_keys<const N: usize, const R: usize>(seed: u32) -> [[u8; N]; R] {
+    let mut keys = [[0u8; N]; R];
+    let mut r = 0;
+    while r < R {
+        keys[r] = gen_keystream::<N>(seed ^ (r as u32).wrapping_mul(0x9e3779b9));
+        r += 1;
+    }
+    keys
+}
+
+const fn round_shift(r: usize) -> u32 {
+    (r % 7) as u32 + 1
+}
+
+pub const fn encrypt_rounds<const N: usize, const R: usize>(data: &[u8], keys: &[[u8; N]; R]) -> [u8; N] {
+    let mut result = [0u8; N];
+    let mut i = 0;
+    while i < N {
+        let mut b = data[i];
+        let mut r = 0;
+        while r < R {
+            b ^= keys[r][i];
+            if r > 0 {
+                b = b.rotate_left(round_shift(r));
+            }
+            r += 1;
+        }
+        result[i] = b;
+        i += 1;
+    }
+    result
+}
+
+pub fn decrypt_rounds<const N: usize, const R: usize>(data: &[u8; N], keys: &[[u8; N]; R]) -> [u8; N] {
+    let (first, rest) = match keys.split_first() {
+        Some(split) => split,
+        None => return *data,
+    };
+    if rest.is_empty() {
+        return decrypt_bytes(data, first);
+    }
+    let mut buf = unsafe { read_volatile(data) };
+    for (r, keys) in rest.iter().enumerate().rev() {
+        for (b, k) in buf.iter_mut().zip(keys.iter()) {
+            *b = b.rotate_right(round_shift(r + 1)) ^ k;
+        }
+    }
+    decrypt_bytes(&buf, first)
+}
+
 #[inline(always)]
 pub fn bytes_to_str(bytes: &[u8]) -> &str {
     #[cfg(debug_assertions)]
@@ -72,27 +132,152 @@ pub fn bytes_to_str(bytes: &[u8]) -> &str {
     return unsafe { core::str::from_utf8_unchecked(bytes) };
 }
 
+pub struct DecryptedStr<const N: usize> {
+    bytes: [u8; N],
+}
+
+impl<const N: usize> DecryptedStr<N> {
+    #[inline(always)]
+    pub fn new(bytes: [u8; N]) -> Self {
+        Self { bytes }
+    }
+
+    #[inline(always)]
+    pub fn as_str(&self) -> &str {
+        bytes_to_str(&self.bytes)
+    }
+}
+
+impl<const N: usize> core::ops::Deref for DecryptedStr<N> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> AsRef<str> for DecryptedStr<N> {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> PartialEq<str> for DecryptedStr<N> {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<const N: usize> PartialEq<&str> for DecryptedStr<N> {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl<const N: usize> core::fmt::Display for DecryptedStr<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl<const N: usize> core::fmt::Debug for DecryptedStr<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+pub struct StrCache(std::sync::OnceLock<Box<str>>);
+
+impl StrCache {
+    pub const fn new() -> Self {
+        Self(std::sync::OnceLock::new())
+    }
+
+    pub fn get<S: AsRef<str>>(&'static self, decrypt: impl FnOnce() -> S) -> &'static str {
+        self.0.get_or_init(|| Box::from(decrypt().as_ref()))
+    }
+}
+
+impl Default for StrCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A string stored inline in `N` bytes, for plain-data containers such as `obf_static!`
+/// that cannot hold `&str`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct FixedStr<const N: usize> {
+    len: [u8; 4],
+    bytes: [u8; N],
+}
+
+unsafe impl<const N: usize> crate::value::NoUninit for FixedStr<N> {}
+
+impl<const N: usize> FixedStr<N> {
+    pub const fn new(s: &str) -> Self {
+        let src = s.as_bytes();
+        assert!(src.len() <= N, "FixedStr: string does not fit");
+        let mut bytes = [0u8; N];
+        let mut i = 0;
+        while i < src.len() {
+            bytes[i] = src[i];
+            i += 1;
+        }
+        Self { len: (src.len() as u32).to_le_bytes(), bytes }
+    }
+
+    pub fn as_str(&self) -> &str {
+        let len = (u32::from_le_bytes(self.len) as usize).min(N);
+        core::str::from_utf8(&self.bytes[..len]).unwrap_or("")
+    }
+}
+
+impl<const N: usize> core::ops::Deref for FixedStr<N> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> core::fmt::Debug for FixedStr<N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+#[cfg(not(feature = "passthrough"))]
 #[macro_export]
 macro_rules! obf_str {
-    ($s:expr) => {{
+    (level = $lvl:expr; $s:expr) => {{
         const _SRC: &[u8] = $s.as_bytes();
         const _LEN: usize = _SRC.len();
-        const _KEYS: [u8; _LEN] = $crate::string::gen_keystream::<_LEN>(
+        const _ROUNDS: usize = $crate::level::string_rounds($lvl);
+        const _KEYS: [[u8; _LEN]; _ROUNDS] = $crate::string::gen_round_keys::<_LEN, _ROUNDS>(
             $crate::ct_rand!(u32, stringify!($s)));
-        static _ENC: [u8; _LEN] = $crate::string::encrypt_bytes::<_LEN>(_SRC, &_KEYS);
-        $crate::string::bytes_to_str(&$crate::string::decrypt_bytes::<_LEN>(&_ENC, &_KEYS))
+        static _ENC: [u8; _LEN] = $crate::string::encrypt_rounds::<_LEN, _ROUNDS>(_SRC, &_KEYS);
+        &*$crate::string::DecryptedStr::new($crate::string::decrypt_rounds::<_LEN, _ROUNDS>(&_ENC, &_KEYS))
     }};
+    ($s:expr) => {
+        $crate::obf_str!(level = $crate::level::OBF_LEVEL; $s)
+    };
 }
 
+#[cfg(not(feature = "passthrough"))]
 #[macro_export]
 macro_rules! obf_bytes {
-    ($s:expr) => {{
+    (level = $lvl:expr; $s:expr) => {{
         const _SRC: &[u8] = $s;
         const _LEN: usize = _SRC.len();
-        const _KEYS: [u8; _LEN] = $crate::string::gen_keystream::<_LEN>(
+        const _ROUNDS: usize = $crate::level::string_rounds($lvl);
+        const _KEYS: [[u8; _LEN]; _ROUNDS] = $crate::string::gen_round_keys::<_LEN, _ROUNDS>(
             $crate::ct_rand!(u32, stringify!($s)));
-        static _ENC: [u8; _LEN] = $crate::string::encrypt_bytes::<_LEN>(_SRC, &_KEYS);
-        $crate::string::decrypt_bytes::<_LEN>(&_ENC, &_KEYS)
+        static _ENC: [u8; _LEN] = $crate::string::encrypt_rounds::<_LEN, _ROUNDS>(_SRC, &_KEYS);
+        $crate::string::decrypt_rounds::<_LEN, _ROUNDS>(&_ENC, &_KEYS)
     }};
+    ($s:expr) => {
+        $crate::obf_bytes!(level = $crate::level::OBF_LEVEL; $s)
+    };
 }
-
@@ -0,0 +1,434 @@
+use core::fmt;
+use core::hint::black_box;
+use core::mem::{size_of, MaybeUninit};
+use core::ptr::{read_volatile, write_volatile};
+use core::sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::RwLock;
+
+use crate::encoding::{mba_decode_u32, mba_decode_u64, mba_encode_u32, mba_encode_u64};
+
+use crate::rng::{runtime_key, runtime_seed, xxhash_mix};
+
+/// # Safety
+/// Every byte of `Self` must be initialized: no padding, no `MaybeUninit` fields.
+pub unsafe trait NoUninit: Copy + 'static {}
+
+macro_rules! no_uninit {
+    ($($ty:ty),*) => {
+        $(unsafe impl NoUninit for $ty {})*
+    };
+}
+
+no_uninit!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64, bool, char, ());
+
+unsafe impl<T: NoUninit, const N: usize> NoUninit for [T; N] {}
+
+#[inline(never)]
+fn apply_key<T: NoUninit>(slot: &mut MaybeUninit<T>, key: u64) {
+    let ptr = slot.as_mut_ptr() as *mut u8;
+    let key = black_box(key) ^ runtime_seed();
+    let mut mask = 0u64;
+    for i in 0..size_of::<T>() {
+        if i % 8 == 0 {
+            mask = xxhash_mix(key ^ (i as u64 / 8).wrapping_mul(0x9e3779b97f4a7c15));
+        }
+        unsafe { write_volatile(ptr.add(i), read_volatile(ptr.add(i)) ^ (mask >> (i % 8 * 8)) as u8) };
+    }
+}
+
+fn wipe<T>(slot: &mut MaybeUninit<T>) {
+    let ptr = slot.as_mut_ptr() as *mut u8;
+    for i in 0..size_of::<T>() {
+        unsafe { write_volatile(ptr.add(i), 0) };
+    }
+}
+
+pub struct ObfuscatedValue<T: NoUninit> {
+    data: MaybeUninit<T>,
+    key: u64,
+}
+
+impl<T: NoUninit> ObfuscatedValue<T> {
+    pub fn new(val: T) -> Self {
+        let key = runtime_key();
+        let mut data = MaybeUninit::new(val);
+        apply_key(&mut data, key);
+        Self { data, key }
+    }
+
+    pub fn get(&self) -> T {
+        let mut plain = self.data;
+        apply_key(&mut plain, self.key);
+        unsafe { plain.assume_init() }
+    }
+
+    pub fn set(&mut self, val: T) {
+        wipe(&mut self.data);
+        let key = runtime_key();
+        self.data = MaybeUninit::new(val);
+        apply_key(&mut self.data, key);
+        self.key = key;
+    }
+
+    pub fn replace(&mut self, val: T) -> T {
+        let old = self.get();
+        self.set(val);
+        old
+    }
+
+    pub fn swap(&mut self, other: &mut Self) {
+        let mine = self.get();
+        self.set(other.get());
+        other.set(mine);
+    }
+
+    pub fn update(&mut self, f: impl FnOnce(T) -> T) -> T {
+        let val = f(self.get());
+        self.set(val);
+        val
+    }
+
+    pub fn with<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        let mut plain = self.data;
+        apply_key(&mut plain, self.key);
+        let result = f(unsafe { plain.assume_init_ref() });
+        wipe(&mut plain);
+        result
+    }
+}
+
+impl<T: NoUninit> Clone for ObfuscatedValue<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.get())
+    }
+}
+
+impl<T: NoUninit + Default> Default for ObfuscatedValue<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: NoUninit> From<T> for ObfuscatedValue<T> {
+    fn from(val: T) -> Self {
+        Self::new(val)
+    }
+}
+
+impl<T: NoUninit> fmt::Debug for ObfuscatedValue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ObfuscatedValue(<redacted>)")
+    }
+}
+
+impl<T: NoUninit> Drop for ObfuscatedValue<T> {
+    fn drop(&mut self) {
+        wipe(&mut self.data);
+        unsafe { write_volatile(&mut self.key, 0) };
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TamperEvent {
+    pub copies: usize,
+    pub intact: usize,
+}
+
+impl fmt::Display for TamperEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "guarded value tampered: {} of {} copies intact", self.intact, self.copies)
+    }
+}
+
+impl std::error::Error for TamperEvent {}
+
+fn default_tamper_hook(event: &TamperEvent) {
+    panic!("{}", event);
+}
+
+static TAMPER_HOOK: RwLock<fn(&TamperEvent)> = RwLock::new(default_tamper_hook);
+
+pub fn set_tamper_hook(hook: fn(&TamperEvent)) {
+    *TAMPER_HOOK.write().unwrap_or_else(|e| e.into_inner()) = hook;
+}
+
+fn global_tamper_hook(event: &TamperEvent) {
+    let hook = *TAMPER_HOOK.read().unwrap_or_else(|e| e.into_inner());
+    hook(event)
+}
+
+#[inline(never)]
+fn checksum<T: NoUninit>(val: &T, key: u64) -> u64 {
+    let ptr = val as *const T as *const u8;
+    let mut h = black_box(key);
+    for i in 0..size_of::<T>() {
+        h = xxhash_mix(h ^ unsafe { read_volatile(ptr.add(i)) } as u64 ^ ((i as u64) << 8));
+    }
+    h
+}
+
+pub struct GuardedValue<T: NoUninit, const COPIES: usize = 2> {
+    copies: [ObfuscatedValue<T>; COPIES],
+    sum: ObfuscatedValue<u64>,
+    sum_key: u64,
+    hook: Option<fn(&TamperEvent)>,
+}
+
+impl<T: NoUninit, const COPIES: usize> GuardedValue<T, COPIES> {
+    pub fn new(val: T) -> Self {
+        assert!(COPIES >= 2, "GuardedValue needs at least two copies");
+        let sum_key = runtime_key();
+        Self {
+            copies: core::array::from_fn(|_| ObfuscatedValue::new(val)),
+            sum: ObfuscatedValue::new(checksum(&val, sum_key)),
+            sum_key,
+            hook: None,
+        }
+    }
+
+    pub fn with_hook(val: T, hook: fn(&TamperEvent)) -> Self {
+        let mut guarded = Self::new(val);
+        guarded.hook = Some(hook);
+        guarded
+    }
+
+    pub fn try_get(&self) -> Result<T, TamperEvent> {
+        let sum = self.sum.get();
+        let mut first = None;
+        let mut intact = 0;
+        for copy in &self.copies {
+            let val = copy.get();
+            if checksum(&val, self.sum_key) == sum {
+                intact += 1;
+                first.get_or_insert(val);
+            }
+        }
+        match first {
+            Some(val) if intact == COPIES => Ok(val),
+            _ => Err(TamperEvent { copies: COPIES, intact }),
+        }
+    }
+
+    pub fn get(&self) -> T {
+        match self.try_get() {
+            Ok(val) => val,
+            Err(event) => {
+                match self.hook {
+                    Some(hook) => hook(&event),
+                    None => global_tamper_hook(&event),
+                }
+                let sum = self.sum.get();
+                self.copies
+                    .iter()
+                    .map(|copy| copy.get())
+                    .find(|val| checksum(val, self.sum_key) == sum)
+                    .unwrap_or_else(|| panic!("{}", event))
+            }
+        }
+    }
+
+    pub fn set(&mut self, val: T) {
+        self.sum_key = runtime_key();
+        self.sum.set(checksum(&val, self.sum_key));
+        for copy in &mut self.copies {
+            copy.set(val);
+        }
+    }
+
+    pub fn update(&mut self, f: impl FnOnce(T) -> T) -> T {
+        let val = f(self.get());
+        self.set(val);
+        val
+    }
+
+    pub fn replace(&mut self, val: T) -> T {
+        let old = self.get();
+        self.set(val);
+        old
+    }
+
+    pub fn is_intact(&self) -> bool {
+        self.try_get().is_ok()
+    }
+
+    #[doc(hidden)]
+    pub fn corrupt_copy(&mut self, index: usize) {
+        let data = &mut self.copies[index].data;
+        let ptr = data.as_mut_ptr() as *mut u8;
+        unsafe { write_volatile(ptr, read_volatile(ptr) ^ 0x40) };
+    }
+}
+
+impl<T: NoUninit, const COPIES: usize> Clone for GuardedValue<T, COPIES> {
+    fn clone(&self) -> Self {
+        let mut guarded = Self::new(self.get());
+        guarded.hook = self.hook;
+        guarded
+    }
+}
+
+impl<T: NoUninit, const COPIES: usize> fmt::Debug for GuardedValue<T, COPIES> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("GuardedValue(<redacted>)")
+    }
+}
+
+#[cfg(target_pointer_width = "64")]
+const fn mba_encode_usize(val: usize, key: usize) -> usize {
+    mba_encode_u64(val as u64, key as u64) as usize
+}
+
+#[cfg(target_pointer_width = "64")]
+const fn mba_decode_usize(val: usize, key: usize) -> usize {
+    mba_decode_u64(val as u64, key as u64) as usize
+}
+
+#[cfg(not(target_pointer_width = "64"))]
+const fn mba_encode_usize(val: usize, key: usize) -> usize {
+    mba_encode_u32(val as u32, key as u32) as usize
+}
+
+#[cfg(not(target_pointer_width = "64"))]
+const fn mba_decode_usize(val: usize, key: usize) -> usize {
+    mba_decode_u32(val as u32, key as u32) as usize
+}
+
+macro_rules! obf_atomic {
+    ($name:ident, $atomic:ident, $ty:ident, $encode:ident, $decode:ident) => {
+        pub struct $name {
+            enc: $atomic,
+            key: $atomic,
+        }
+
+        impl $name {
+            pub fn new(val: $ty) -> Self {
+                let key = runtime_key() as $ty;
+                Self { enc: $atomic::new($encode(val, key)), key: $atomic::new(key) }
+            }
+
+            #[inline(always)]
+            fn key(&self) -> $ty {
+                black_box(self.key.load(Ordering::Relaxed))
+            }
+
+            pub fn load(&self, order: Ordering) -> $ty {
+                $decode(self.enc.load(order), self.key())
+            }
+
+            pub fn store(&self, val: $ty, order: Ordering) {
+                self.enc.store($encode(val, self.key()), order)
+            }
+
+            pub fn swap(&self, val: $ty, order: Ordering) -> $ty {
+                let key = self.key();
+                $decode(self.enc.swap($encode(val, key), order), key)
+            }
+
+            pub fn compare_exchange(
+                &self,
+                current: $ty,
+                new: $ty,
+                success: Ordering,
+                failure: Ordering,
+            ) -> Result<$ty, $ty> {
+                let key = self.key();
+                self.enc
+                    .compare_exchange($encode(current, key), $encode(new, key), success, failure)
+                    .map(|v| $decode(v, key))
+                    .map_err(|v| $decode(v, key))
+            }
+
+            pub fn compare_exchange_weak(
+                &self,
+                current: $ty,
+                new: $ty,
+                success: Ordering,
+                failure: Ordering,
+            ) -> Result<$ty, $ty> {
+                let key = self.key();
+                self.enc
+                    .compare_exchange_weak($encode(current, key), $encode(new, key), success, failure)
+                    .map(|v| $decode(v, key))
+                    .map_err(|v| $decode(v, key))
+            }
+
+            pub fn fetch_update(
+                &self,
+                set_order: Ordering,
+                fetch_order: Ordering,
+                mut f: impl FnMut($ty) -> Option<$ty>,
+            ) -> Result<$ty, $ty> {
+                let key = self.key();
+                self.enc
+                    .fetch_update(set_order, fetch_order, |enc| f($decode(enc, key)).map(|v| $encode(v, key)))
+                    .map(|v| $decode(v, key))
+                    .map_err(|v| $decode(v, key))
+            }
+
+            pub fn fetch_add(&self, val: $ty, order: Ordering) -> $ty {
+                self.rmw(order, |v| v.wrapping_add(val))
+            }
+
+            pub fn fetch_sub(&self, val: $ty, order: Ordering) -> $ty {
+                self.rmw(order, |v| v.wrapping_sub(val))
+            }
+
+            pub fn fetch_and(&self, val: $ty, order: Ordering) -> $ty {
+                self.rmw(order, |v| v & val)
+            }
+
+            pub fn fetch_or(&self, val: $ty, order: Ordering) -> $ty {
+                self.rmw(order, |v| v | val)
+            }
+
+            pub fn fetch_xor(&self, val: $ty, order: Ordering) -> $ty {
+                self.rmw(order, |v| v ^ val)
+            }
+
+            pub fn fetch_max(&self, val: $ty, order: Ordering) -> $ty {
+                self.rmw(order, |v| v.max(val))
+            }
+
+            pub fn fetch_min(&self, val: $ty, order: Ordering) -> $ty {
+                self.rmw(order, |v| v.min(val))
+            }
+
+            fn rmw(&self, order: Ordering, f: impl Fn($ty) -> $ty) -> $ty {
+                let fetch = match order {
+                    Ordering::Release | Ordering::Relaxed => Ordering::Relaxed,
+                    Ordering::SeqCst => Ordering::SeqCst,
+                    _ => Ordering::Acquire,
+                };
+                match self.fetch_update(order, fetch, |v| Some(f(v))) {
+                    Ok(v) | Err(v) => v,
+                }
+            }
+
+            pub fn into_inner(self) -> $ty {
+                $decode(self.enc.into_inner(), self.key.into_inner())
+            }
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self::new(0)
+            }
+        }
+
+        impl From<$ty> for $name {
+            fn from(val: $ty) -> Self {
+                Self::new(val)
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(concat!(stringify!($name), "(<redacted>)"))
+            }
+        }
+    };
+}
+
+obf_atomic!(ObfAtomicU32, AtomicU32, u32, mba_encode_u32, mba_decode_u32);
+obf_atomic!(ObfAtomicU64, AtomicU64, u64, mba_encode_u64, mba_decode_u64);
+obf_atomic!(ObfAtomicUsize, AtomicUsize, usize, mba_encode_usize, mba_decode_usize);
@@ -7,7 +7,7 @@ const fn utf8_char_len(first: u8) -> usize {
 }
 
 const fn decode_utf8(bytes: &[u8]) -> Option<(u32, usize)> {
-    if bytes.len() == 0 { return None; }
+    if bytes.is_empty() { return None; }
     let len = utf8_char_len(bytes[0]);
     if bytes.len() < len { return None; }
     
@@ -111,15 +111,22 @@ pub fn decrypt_wide<const N: usize>(data: &[u16; N], keys: &[u16; N]) -> [u16; N
     result
 }
 
+#[cfg(not(feature = "passthrough"))]
 #[macro_export]
 macro_rules! obf_wide {
-    ($s:expr) => {{
+    (level = $lvl:expr; $s:expr) => {{
         const _LEN: usize = $crate::wide::utf16_len($s);
         const _SRC: [u16; _LEN] = $crate::wide::encode_utf16::<_LEN>($s);
-        const _KEYS: [u16; _LEN] = $crate::wide::gen_wide_keys::<_LEN>(
-            $crate::ct_rand!(u32, stringify!($s)));
+        const _KEYS: [u16; _LEN] = if $crate::level::string_rounds($lvl) == 0 {
+            [0u16; _LEN]
+        } else {
+            $crate::wide::gen_wide_keys::<_LEN>($crate::ct_rand!(u32, stringify!($s)))
+        };
         static _ENC: [u16; _LEN] = $crate::wide::encrypt_wide::<_LEN>(&_SRC, &_KEYS);
         $crate::wide::decrypt_wide::<_LEN>(&_ENC, &_KEYS)
     }};
+    ($s:expr) => {
+        $crate::obf_wide!(level = $crate::level::OBF_LEVEL; $s)
+    };
 }
 
@@ -0,0 +1,128 @@
+use rust_native_obf::blob::{self, BLOB_MAGIC, HEADER_LEN};
+use rust_native_obf::*;
+
+const KEY: &[u8] = b"golden-key-v1";
+const MESSAGE: &[u8] = b"the quick brown fox jumps over the lazy dog";
+
+const V1_CASCADE: &[u8] = include_bytes!("golden/v1_cascade.bin");
+const V1_CASCADE_R9: &[u8] = include_bytes!("golden/v1_cascade_r9.bin");
+const V1_XOR: &[u8] = include_bytes!("golden/v1_xor.bin");
+const V1_EMPTY: &[u8] = include_bytes!("golden/v1_empty.bin");
+
+#[test]
+fn golden_blobs_decode() {
+    let decoder = BlobDecoder::new(KEY);
+    assert_eq!(decoder.decode(V1_CASCADE).unwrap(), MESSAGE);
+    assert_eq!(decoder.decode(V1_CASCADE_R9).unwrap(), MESSAGE);
+    assert_eq!(decoder.decode(V1_XOR).unwrap(), MESSAGE);
+    assert_eq!(decoder.decode(V1_EMPTY).unwrap(), b"");
+}
+
+#[test]
+fn golden_headers() {
+    let header = BlobHeader::parse(V1_CASCADE).unwrap();
+    assert_eq!(header, BlobHeader { version: 1, cipher: CipherId::Cascade, rounds: 4, nonce: 0x0123456789abcdef, len: 43 });
+    let header = BlobHeader::parse(V1_XOR).unwrap();
+    assert_eq!((header.cipher, header.nonce), (CipherId::Xor, 0xfeedface));
+    assert_eq!(BlobHeader::parse(V1_CASCADE_R9).unwrap().rounds, 9);
+}
+
+#[test]
+fn encoder_reproduces_golden_blobs() {
+    let encoder = BlobEncoder::new(KEY).nonce(0x0123456789abcdef);
+    assert_eq!(encoder.encode(MESSAGE).unwrap(), V1_CASCADE);
+    let encoder = BlobEncoder::new(KEY).rounds(9).nonce(7);
+    assert_eq!(encoder.encode(MESSAGE).unwrap(), V1_CASCADE_R9);
+    let encoder = BlobEncoder::new(KEY).cipher(CipherId::Xor).nonce(0xfeedface);
+    assert_eq!(encoder.encode(MESSAGE).unwrap(), V1_XOR);
+    assert_eq!(BlobEncoder::new(KEY).nonce(1).encode(b"").unwrap(), V1_EMPTY);
+}
+
+#[test]
+fn payload_is_not_plaintext() {
+    let body = &V1_CASCADE[HEADER_LEN..HEADER_LEN + MESSAGE.len()];
+    assert_ne!(body, MESSAGE);
+    let fresh = BlobEncoder::new(KEY).encode(MESSAGE).unwrap();
+    assert_eq!(BlobDecoder::new(KEY).decode(&fresh).unwrap(), MESSAGE);
+}
+
+#[test]
+fn truncated() {
+    let decoder = BlobDecoder::new(KEY);
+    assert_eq!(decoder.decode(&V1_CASCADE[..10]), Err(BlobError::Truncated { needed: HEADER_LEN, got: 10 }));
+    let cut = V1_CASCADE.len() - 3;
+    assert_eq!(
+        decoder.decode(&V1_CASCADE[..cut]),
+        Err(BlobError::Truncated { needed: V1_CASCADE.len(), got: cut })
+    );
+    let mut long = V1_CASCADE.to_vec();
+    long.push(0);
+    assert_eq!(decoder.decode(&long), Err(BlobError::TrailingData(1)));
+}
+
+#[test]
+fn bad_magic_version_and_cipher() {
+    let decoder = BlobDecoder::new(KEY);
+    let mut blob = V1_CASCADE.to_vec();
+    blob[0] = b'X';
+    assert_eq!(decoder.decode(&blob), Err(BlobError::BadMagic([b'X', BLOB_MAGIC[1], BLOB_MAGIC[2], BLOB_MAGIC[3]])));
+
+    let mut blob = V1_CASCADE.to_vec();
+    blob[4] = 9;
+    assert_eq!(decoder.decode(&blob), Err(BlobError::UnsupportedVersion(9)));
+
+    let mut blob = V1_CASCADE.to_vec();
+    blob[5] = 0x7f;
+    assert_eq!(decoder.decode(&blob), Err(BlobError::UnknownCipher(0x7f)));
+}
+
+#[test]
+fn mac_failure() {
+    let mut blob = V1_CASCADE.to_vec();
+    blob[HEADER_LEN + 3] ^= 1;
+    assert_eq!(BlobDecoder::new(KEY).decode(&blob), Err(BlobError::MacMismatch));
+
+    let mut blob = V1_CASCADE.to_vec();
+    blob[6] = 5;
+    assert_eq!(BlobDecoder::new(KEY).decode(&blob), Err(BlobError::MacMismatch));
+
+    assert_eq!(BlobDecoder::new(b"wrong key").decode(V1_CASCADE), Err(BlobError::MacMismatch));
+}
+
+#[test]
+fn empty_key() {
+    assert_eq!(BlobEncoder::new(b"").encode(MESSAGE), Err(BlobError::EmptyKey));
+    assert_eq!(BlobDecoder::new(b"").decode(V1_CASCADE), Err(BlobError::EmptyKey));
+}
+
+#[test]
+fn every_bit_flip_is_caught() {
+    let decoder = BlobDecoder::new(KEY);
+    for i in 0..V1_CASCADE.len() {
+        for bit in 0..8 {
+            let mut blob = V1_CASCADE.to_vec();
+            blob[i] ^= 1 << bit;
+            assert!(decoder.decode(&blob).is_err(), "byte {} bit {}", i, bit);
+        }
+    }
+}
+
+#[test]
+fn tag_depends_on_whole_key() {
+    let blob = BlobEncoder::new(KEY).nonce(3).encode(MESSAGE).unwrap();
+    for i in 0..KEY.len() {
+        let mut key = KEY.to_vec();
+        key[i] ^= 0x80;
+        assert_eq!(BlobDecoder::new(&key).decode(&blob), Err(BlobError::MacMismatch));
+    }
+    assert_eq!(BlobDecoder::new(&[KEY, b"\0"].concat()).decode(&blob), Err(BlobError::MacMismatch));
+}
+
+#[test]
+#[cfg(target_pointer_width = "64")]
+fn huge_declared_length_is_truncated() {
+    let mut blob = V1_EMPTY.to_vec();
+    blob[16..20].copy_from_slice(&u32::MAX.to_le_bytes());
+    let needed = HEADER_LEN + u32::MAX as usize + blob::MAC_LEN;
+    assert_eq!(BlobDecoder::new(KEY).decode(&blob), Err(BlobError::Truncated { needed, got: blob.len() }));
+}
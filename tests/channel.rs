@@ -0,0 +1,135 @@
+#![cfg(unix)]
+
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::thread;
+
+use rust_native_obf::*;
+
+const SEED: u64 = channel_seed!("channel-test");
+
+fn connect() -> (ObfChannel<UnixStream>, ObfChannel<UnixStream>) {
+    connect_with(SEED, SEED)
+}
+
+fn connect_with(a_seed: u64, b_seed: u64) -> (ObfChannel<UnixStream>, ObfChannel<UnixStream>) {
+    let (a, b) = UnixStream::pair().unwrap();
+    let peer = thread::spawn(move || ObfChannel::handshake(b, b_seed).unwrap());
+    let a = ObfChannel::handshake(a, a_seed).unwrap();
+    (a, peer.join().unwrap())
+}
+
+fn frame_len(msg: &[u8]) -> usize {
+    12 + msg.len() + 8
+}
+
+fn capture(from: &mut UnixStream, len: usize) -> Vec<u8> {
+    let mut frame = vec![0u8; len];
+    from.read_exact(&mut frame).unwrap();
+    frame
+}
+
+#[test]
+fn round_trip_both_directions() {
+    let (mut a, mut b) = connect();
+    for i in 0..50u32 {
+        let msg = format!("ping {}", i).repeat(i as usize % 7 + 1);
+        a.send(msg.as_bytes()).unwrap();
+        assert_eq!(b.recv().unwrap(), msg.as_bytes());
+        b.send(&i.to_le_bytes()).unwrap();
+        assert_eq!(a.recv().unwrap(), i.to_le_bytes());
+    }
+    a.send(b"").unwrap();
+    assert_eq!(b.recv().unwrap(), b"");
+}
+
+#[test]
+fn traffic_is_not_plaintext() {
+    let (mut a, b) = connect();
+    let mut raw = b.into_inner();
+    let msg = b"secret helper command: unlock";
+    a.send(msg).unwrap();
+    let frame = capture(&mut raw, frame_len(msg));
+    assert!(!frame.windows(msg.len()).any(|w| w == msg));
+
+    a.send(msg).unwrap();
+    let again = capture(&mut raw, frame_len(msg));
+    assert_ne!(frame[12..], again[12..]);
+}
+
+#[test]
+fn replayed_frame_is_rejected() {
+    let (mut a, b) = connect();
+    let mut inject = a.get_ref().try_clone().unwrap();
+    let mut tap = b.get_ref().try_clone().unwrap();
+    let mut b = b;
+
+    a.send(b"transfer 10").unwrap();
+    let frame = capture(&mut tap, frame_len(b"transfer 10"));
+    inject.write_all(&frame).unwrap();
+    inject.write_all(&frame).unwrap();
+
+    assert_eq!(b.recv().unwrap(), b"transfer 10");
+    match b.recv() {
+        Err(ChannelError::Replayed { expected: 1, got: 0 }) => {}
+        other => panic!("expected replay error, got {:?}", other),
+    }
+
+    a.send(b"next").unwrap();
+    assert_eq!(b.recv().unwrap(), b"next");
+}
+
+#[test]
+fn reordered_frames_are_rejected() {
+    let (mut a, b) = connect();
+    let mut inject = a.get_ref().try_clone().unwrap();
+    let mut tap = b.get_ref().try_clone().unwrap();
+    let mut b = b;
+
+    a.send(b"first").unwrap();
+    a.send(b"second").unwrap();
+    let first = capture(&mut tap, frame_len(b"first"));
+    let second = capture(&mut tap, frame_len(b"second"));
+    inject.write_all(&second).unwrap();
+    inject.write_all(&first).unwrap();
+
+    match b.recv() {
+        Err(ChannelError::OutOfOrder { expected: 0, got: 1 }) => {}
+        other => panic!("expected reorder error, got {:?}", other),
+    }
+    assert_eq!(b.recv().unwrap(), b"first");
+}
+
+#[test]
+fn tampered_frame_fails_mac() {
+    let (mut a, b) = connect();
+    let mut inject = a.get_ref().try_clone().unwrap();
+    let mut tap = b.get_ref().try_clone().unwrap();
+    let mut b = b;
+
+    a.send(b"balance=100").unwrap();
+    let mut frame = capture(&mut tap, frame_len(b"balance=100"));
+    frame[14] ^= 0x01;
+    inject.write_all(&frame).unwrap();
+    assert!(matches!(b.recv(), Err(ChannelError::BadMac)));
+}
+
+#[test]
+fn mismatched_seed_fails_mac() {
+    let (mut a, mut b) = connect_with(SEED, channel_seed!("other-build"));
+    a.send(b"hello").unwrap();
+    assert!(matches!(b.recv(), Err(ChannelError::BadMac)));
+}
+
+#[test]
+fn frames_from_another_session_are_rejected() {
+    let (mut a1, b1) = connect();
+    let mut tap = b1.get_ref().try_clone().unwrap();
+    a1.send(b"old session").unwrap();
+    let old = capture(&mut tap, frame_len(b"old session"));
+
+    let (a2, mut b2) = connect();
+    let mut inject = a2.get_ref().try_clone().unwrap();
+    inject.write_all(&old).unwrap();
+    assert!(matches!(b2.recv(), Err(ChannelError::BadMac)));
+}
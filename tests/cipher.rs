@@ -0,0 +1,140 @@
+use std::io::{Read, Write};
+
+use rust_native_obf::*;
+
+const KEY: &[u8] = b"cascade test key";
+
+fn message(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 31 + i / 7) as u8).collect()
+}
+
+fn seal(data: &[u8], chunk: usize) -> Vec<u8> {
+    let mut w = CascadeWriter::new(Vec::new(), KEY, 5);
+    for part in data.chunks(chunk) {
+        w.write_all(part).unwrap();
+    }
+    w.into_inner().unwrap()
+}
+
+fn open_with(sealed: &[u8], key: &[u8], chunk: usize) -> Vec<u8> {
+    let mut r = CascadeReader::new(sealed, key, 5);
+    let mut out = Vec::new();
+    let mut buf = vec![0u8; chunk];
+    loop {
+        let n = r.read(&mut buf).unwrap();
+        if n == 0 {
+            return out;
+        }
+        out.extend_from_slice(&buf[..n]);
+    }
+}
+
+#[test]
+fn round_trip_odd_sizes() {
+    for len in [0, 1, 3, 7, 8, 9, 255, 1021] {
+        let data = message(len);
+        for write_chunk in [1, 3, 5, 13, 64] {
+            let sealed = seal(&data, write_chunk);
+            assert_eq!(sealed.len(), 8 + len);
+            if len > 8 {
+                assert_ne!(&sealed[8..], &data[..]);
+            }
+            for read_chunk in [1, 2, 7, 11, 4096] {
+                assert_eq!(open_with(&sealed, KEY, read_chunk), data, "{} {} {}", len, write_chunk, read_chunk);
+            }
+        }
+    }
+}
+
+#[test]
+fn chunking_does_not_change_ciphertext() {
+    let data = message(300);
+    let cipher = Cascade::new(KEY, 0x1234, 3);
+    let mut whole = Vec::new();
+    CascadeWriter::with_cipher(&mut whole, cipher.clone()).write_all(&data).unwrap();
+    let mut split = CascadeWriter::with_cipher(Vec::new(), cipher.clone());
+    for part in data.chunks(17) {
+        split.write_all(part).unwrap();
+    }
+    assert_eq!(split.into_inner().unwrap(), whole);
+    let mut out = Vec::new();
+    CascadeReader::with_cipher(&whole[..], cipher).read_to_end(&mut out).unwrap();
+    assert_eq!(out, data);
+}
+
+#[test]
+fn wrong_key_garbles() {
+    let data = message(200);
+    let sealed = seal(&data, 9);
+    let garbled = open_with(&sealed, b"cascade test kez", 13);
+    assert_eq!(garbled.len(), data.len());
+    assert_ne!(garbled, data);
+    let diff = garbled.iter().zip(&data).filter(|(a, b)| a != b).count();
+    assert!(diff > data.len() / 2);
+}
+
+#[test]
+fn empty_stream_reads_zero() {
+    let mut r = CascadeReader::new(&[][..], KEY, 5);
+    let mut buf = [0u8; 16];
+    assert_eq!(r.read(&mut buf).unwrap(), 0);
+    let mut out = Vec::new();
+    assert_eq!(r.read_to_end(&mut out).unwrap(), 0);
+}
+
+#[test]
+fn truncated_header_is_eof() {
+    let sealed = seal(b"hello", 5);
+    let mut r = CascadeReader::new(&sealed[..5], KEY, 5);
+    let err = r.read(&mut [0u8; 8]).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+}
+
+struct Flaky {
+    out: Vec<u8>,
+    budget: usize,
+}
+
+impl Write for Flaky {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        if self.budget == 0 {
+            self.budget = 5;
+            return Err(std::io::ErrorKind::WouldBlock.into());
+        }
+        let n = data.len().min(self.budget).min(3);
+        self.budget -= n;
+        self.out.extend_from_slice(&data[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn push(w: &mut CascadeWriter<Flaky>, data: &[u8], chunk: usize) {
+    let mut rest = data;
+    while !rest.is_empty() {
+        match w.write(&rest[..rest.len().min(chunk)]) {
+            Ok(n) => rest = &rest[n..],
+            Err(e) => assert_eq!(e.kind(), std::io::ErrorKind::WouldBlock),
+        }
+    }
+}
+
+#[test]
+fn short_and_failed_writes_stay_in_sync() {
+    let data = message(257);
+    let mut w = CascadeWriter::new(Flaky { out: Vec::new(), budget: 4 }, KEY, 5);
+    push(&mut w, &data, 11);
+    let sealed = &w.get_ref().out;
+    assert_eq!(sealed.len(), 8 + data.len());
+    assert_eq!(open_with(sealed, KEY, 7), data);
+
+    let cipher = Cascade::new(KEY, 0x77, 5);
+    let mut w = CascadeWriter::with_cipher(Flaky { out: Vec::new(), budget: 2 }, cipher.clone());
+    push(&mut w, &data, 64);
+    let mut out = Vec::new();
+    CascadeReader::with_cipher(&w.get_ref().out[..], cipher).read_to_end(&mut out).unwrap();
+    assert_eq!(out, data);
+}
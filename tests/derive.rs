@@ -0,0 +1,40 @@
+use rust_native_obf::*;
+
+#[derive(Obfuscated)]
+struct Session<T> {
+    user_id: u64,
+    extra: T,
+}
+
+#[derive(Obfuscated)]
+struct Token {
+    bytes: [u8; 16],
+    live: bool,
+}
+
+#[test]
+fn generic_struct_round_trips() {
+    let mut s = ObfuscatedSession::new(Session { user_id: 7, extra: [1u16, 2, 3] });
+    assert_eq!(s.user_id(), 7);
+    assert_eq!(s.extra(), [1, 2, 3]);
+    s.set_user_id(8);
+    s.set_extra([4, 5, 6]);
+    let copy = s.clone();
+    let inner = s.into_inner();
+    assert_eq!((inner.user_id, inner.extra), (8, [4, 5, 6]));
+    assert_eq!(copy.user_id(), 8);
+}
+
+#[test]
+fn generic_struct_debug_redacts() {
+    let s: ObfuscatedSession<u32> = Session { user_id: 1, extra: 0xdead }.into();
+    assert_eq!(format!("{:?}", s), "ObfuscatedSession { user_id: <redacted>, extra: <redacted> }");
+}
+
+#[test]
+fn plain_struct_round_trips() {
+    let mut t = ObfuscatedToken::new(Token { bytes: [9; 16], live: true });
+    t.set_live(false);
+    assert_eq!(t.bytes(), [9; 16]);
+    assert!(!t.live());
+}
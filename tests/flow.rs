@@ -0,0 +1,177 @@
+use rust_native_obf::*;
+
+#[allow(clippy::needless_return)]
+fn classify(n: i32) -> &'static str {
+    obf_flatten! {
+        let doubled = n * 2;
+        if doubled < 0 {
+            return "negative";
+        } else if doubled == 0 {
+            "zero"
+        } else if doubled > 100 {
+            "large"
+        } else {
+            "small"
+        }
+    }
+}
+
+fn parse_sum(a: &str, b: &str) -> Result<i64, std::num::ParseIntError> {
+    let total = obf_flatten! {
+        let x: i64 = a.parse()?;
+        let y: i64 = b.parse()?;
+        let mut acc = x;
+        for _ in 0..y {
+            acc += 1;
+        }
+        acc
+    };
+    Ok(total)
+}
+
+#[test]
+fn branches_and_return() {
+    assert_eq!(classify(-4), "negative");
+    assert_eq!(classify(0), "zero");
+    assert_eq!(classify(80), "large");
+    assert_eq!(classify(7), "small");
+}
+
+#[test]
+fn question_mark_propagates() {
+    assert_eq!(parse_sum("40", "2"), Ok(42));
+    assert!(parse_sum("x", "2").is_err());
+    assert!(parse_sum("1", "y").is_err());
+}
+
+#[test]
+fn break_and_continue_in_loop_form() {
+    let mut seen = Vec::new();
+    for i in 0..10 {
+        obf_flatten! { in loop;
+            if i % 2 == 0 {
+                continue;
+            }
+            let sq = i * i;
+            if sq > 40 {
+                break;
+            }
+            seen.push(sq);
+        }
+    }
+    assert_eq!(seen, [1, 9, 25]);
+}
+
+#[test]
+fn labeled_break_and_inner_loops() {
+    let mut hits = 0;
+    'outer: for i in 0..5 {
+        obf_flatten! {
+            let mut j = 0;
+            loop {
+                j += 1;
+                if j == 3 {
+                    break;
+                }
+            }
+            hits += j;
+            if i == 2 {
+                break 'outer;
+            }
+        }
+    }
+    assert_eq!(hits, 9);
+}
+
+#[test]
+fn statements_run_in_order() {
+    let mut log = Vec::new();
+    let out = obf_flatten! {
+        log.push(1);
+        let a = 10;
+        log.push(2);
+        match a {
+            10 => log.push(3),
+            _ => log.push(0),
+        }
+        {
+            log.push(4);
+        }
+        a + log.len()
+    };
+    assert_eq!(log, [1, 2, 3, 4]);
+    assert_eq!(out, 14);
+}
+
+fn early_exit(flag: bool, log: &mut Vec<&'static str>) {
+    obf_if!(branchless flag, {
+        log.push("then");
+        return;
+    });
+    log.push("after");
+}
+
+fn early_value(v: i32) -> i32 {
+    let r = obf_if!(branchless v > 0, { return 100; }, { 0 });
+    r + 1
+}
+
+#[test]
+fn branchless_arms_are_inline() {
+    let mut log = Vec::new();
+    early_exit(true, &mut log);
+    assert_eq!(log, ["then"]);
+    early_exit(false, &mut log);
+    assert_eq!(log, ["then", "after"]);
+    assert_eq!((early_value(5), early_value(-5)), (100, 1));
+
+    let mut total = 0;
+    for i in 0..10 {
+        obf_if!(branchless i % 3 == 0, { continue; });
+        obf_if!(branchless i > 7, { break; }, { total += i; });
+        total += 100;
+    }
+    assert_eq!(total, 1 + 2 + 4 + 5 + 7 + 500);
+}
+
+#[test]
+fn branchless_arms_select() {
+    let mut x = 0;
+    let mut y = 0;
+    for c in [true, false, true] {
+        obf_if!(branchless c, { x += 1; }, { y -= 10; });
+    }
+    assert_eq!((x, y), (2, -10));
+    for c in [true, false] {
+        obf_if!(branchless c, { x += 1; }, { x -= 10; });
+    }
+    assert_eq!(x, -7);
+
+    let owned = String::from("moved");
+    let label = obf_if!(branchless x > y - 10, { owned }, { String::new() });
+    assert_eq!(label, "moved");
+    let lvl0 = obf_if!(level = 0; branchless x > 0, { 1 }, { 2 });
+    assert_eq!(lvl0, 2);
+}
+
+fn bogus_checked(x: u32) -> Result<u32, &'static str> {
+    obf_bogus!([LIMIT: u32 = 100, SALT: u64 = 0x5eed] {
+        if x > LIMIT {
+            return Err("range");
+        }
+        Ok(x ^ SALT as u32)
+    })
+}
+
+#[test]
+fn bogus_runs_the_real_path() {
+    assert_eq!(bogus_checked(7), Ok(7 ^ 0x5eed));
+    assert_eq!(bogus_checked(101), Err("range"));
+    let mut count = 0;
+    for _ in 0..4 {
+        obf_bogus!({
+            count += 1;
+        });
+    }
+    assert_eq!(count, 4);
+}
@@ -0,0 +1,139 @@
+use rust_native_obf::hash::*;
+use rust_native_obf::*;
+
+const EMPTY: &[u8] = b"";
+const NOBODY: &[u8] = b"Nobody inspects the spammish repetition";
+const FOX: &[u8] = b"The quick brown fox jumps over the lazy dog";
+const LONG: &[u8] = b"0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef!";
+
+#[test]
+fn xxh32_vectors() {
+    assert_eq!(xxh32(EMPTY, 0), 0x02cc5d05);
+    assert_eq!(xxh32(b"a", 0), 0x550d7456);
+    assert_eq!(xxh32(b"abc", 0), 0x32d153ff);
+    assert_eq!(xxh32(NOBODY, 0), 0xe2293b2f);
+    assert_eq!(xxh32(FOX, 0), 0xe85ea4de);
+    assert_eq!(xxh32(LONG, 0), 0xf4502801);
+    assert_eq!(xxh32(EMPTY, 0x9e3779b1), 0x36b78ae7);
+    assert_eq!(xxh32(b"abc", 0x9e3779b1), 0xa1ae7709);
+    assert_eq!(xxh32(FOX, 0x9e3779b1), 0x98c7f3bf);
+    assert_eq!(xxh32(LONG, 0x9e3779b1), 0xa2ebb343);
+}
+
+#[test]
+fn xxh64_vectors() {
+    assert_eq!(xxh64(EMPTY, 0), 0xef46db3751d8e999);
+    assert_eq!(xxh64(b"a", 0), 0xd24ec4f1a98c6e5b);
+    assert_eq!(xxh64(b"abc", 0), 0x44bc2cf5ad770999);
+    assert_eq!(xxh64(NOBODY, 0), 0xfbcea83c8a378bf1);
+    assert_eq!(xxh64(FOX, 0), 0x0b242d361fda71bc);
+    assert_eq!(xxh64(LONG, 0), 0x8a86d876ceea0124);
+    assert_eq!(xxh64(EMPTY, 0x9e3779b1), 0xac75fda2929b17ef);
+    assert_eq!(xxh64(NOBODY, 0x9e3779b1), 0x56db22dd5b051147);
+    assert_eq!(xxh64(LONG, 0x9e3779b1), 0xc0640829c941ee6b);
+}
+
+#[test]
+fn fnv1a_vectors() {
+    assert_eq!(fnv1a32(EMPTY), 0x811c9dc5);
+    assert_eq!(fnv1a32(b"a"), 0xe40c292c);
+    assert_eq!(fnv1a32(b"foobar"), 0xbf9cf968);
+    assert_eq!(fnv1a64(EMPTY), 0xcbf29ce484222325);
+    assert_eq!(fnv1a64(b"a"), 0xaf63dc4c8601ec8c);
+    assert_eq!(fnv1a64(b"foobar"), 0x85944171f73967e8);
+}
+
+#[test]
+fn crc32_vectors() {
+    assert_eq!(crc32(EMPTY), 0);
+    assert_eq!(crc32(b"a"), 0xe8b7be43);
+    assert_eq!(crc32(b"123456789"), 0xcbf43926);
+    assert_eq!(crc32(FOX), 0x414fa339);
+    assert_eq!(crc32(LONG), 0x853f5d4c);
+}
+
+#[test]
+fn murmur3_vectors() {
+    assert_eq!(murmur3_32(EMPTY, 0), 0);
+    assert_eq!(murmur3_32(EMPTY, 1), 0x514e28b7);
+    assert_eq!(murmur3_32(b"a", 0), 0x3c2569b2);
+    assert_eq!(murmur3_32(b"abc", 0), 0xb3dd93fa);
+    assert_eq!(murmur3_32(b"abc", 0x9747b28c), 0xc84a62dd);
+    assert_eq!(murmur3_32(FOX, 0x9747b28c), 0x2fa826cd);
+    assert_eq!(murmur3_32(NOBODY, 1), 0x06980cd1);
+    assert_eq!(murmur3_32(LONG, 0), 0x7b842a40);
+}
+
+#[test]
+fn djb2_vectors() {
+    assert_eq!(djb2(EMPTY), 5381);
+    assert_eq!(djb2(b"a"), 177670);
+    assert_eq!(djb2(b"hello"), 261238937);
+}
+
+#[test]
+fn macros_match_functions() {
+    assert_eq!(ct_xxh32!(FOX), 0xe85ea4de);
+    assert_eq!(ct_xxh32!(FOX, 0x9e3779b1), 0x98c7f3bf);
+    assert_eq!(ct_xxh64!(NOBODY), 0xfbcea83c8a378bf1);
+    assert_eq!(ct_fnv1a!(b"foobar"), 0xbf9cf968);
+    assert_eq!(ct_fnv1a64!(b"foobar"), 0x85944171f73967e8);
+    assert_eq!(ct_crc32!(b"123456789"), 0xcbf43926);
+    assert_eq!(ct_murmur3!(FOX, 0x9747b28c), 0x2fa826cd);
+    assert_eq!(ct_djb2!(b"hello"), 261238937);
+}
+
+#[test]
+fn ct_xxhash_keeps_the_original_algorithm() {
+    assert_eq!(ct_xxhash!(b"abc"), xxhash32(b"abc", 0));
+    assert_eq!(ct_xxhash!(FOX, 7), xxhash32(FOX, 7));
+    assert_eq!(xxhash32(b"abc", 0), 0x656856c9);
+    assert_eq!(xxhash32(b"tamper_check", 0), 0xe4c901f4);
+    assert_ne!(xxhash32(LONG, 0), xxh32(LONG, 0));
+}
+
+fn wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().collect()
+}
+
+#[test]
+fn case_insensitive_hashes() {
+    assert_eq!(ct_xxh32_ci!(b"KERNEL32.DLL"), xxh32(b"kernel32.dll", 0));
+    assert_eq!(ct_xxh32_ci!(b"KERNEL32.DLL", 5), xxh32_ci(b"Kernel32.dll", 5));
+    assert_eq!(xxh32_ci(LONG, 0), xxh32(LONG, 0));
+    assert_eq!(xxh32_ci(FOX, 0), xxh32(b"the quick brown fox jumps over the lazy dog", 0));
+    assert_eq!(ct_sdbm_ci!("GetProcAddress"), sdbm_hash("getprocaddress"));
+    assert_eq!(sdbm_hash_ci("Status"), sdbm_hash_ci("STATUS"));
+    assert_ne!(sdbm_hash("Status"), sdbm_hash("STATUS"));
+}
+
+#[test]
+fn utf16_hashes_match_runtime_encoding() {
+    for s in ["", "ntdll.dll", "Größe ✓ 𝄞 wide", "The quick brown fox jumps over the lazy dog"] {
+        let units = wide(s);
+        let bytes: Vec<u8> = units.iter().flat_map(|u| u.to_le_bytes()).collect();
+        assert_eq!(xxh32_utf16(&units, 0), xxh32(&bytes, 0));
+        assert_eq!(xxh32_utf16(&units, 9), xxh32(&bytes, 9));
+    }
+    assert_eq!(ct_xxh32_utf16!("ntdll.dll"), xxh32_utf16(&wide("ntdll.dll"), 0));
+    assert_eq!(ct_xxh32_utf16!("Größe ✓ 𝄞 wide", 3), xxh32_utf16(&wide("Größe ✓ 𝄞 wide"), 3));
+    assert_eq!(ct_xxh32_utf16_ci!("NTDLL.DLL"), xxh32_utf16(&wide("ntdll.dll"), 0));
+    assert_eq!(ct_sdbm_utf16!("LoadLibraryW"), sdbm_utf16(&wide("LoadLibraryW")));
+    assert_eq!(ct_sdbm_utf16!("LoadLibraryW"), sdbm_hash("LoadLibraryW"));
+    assert_eq!(ct_sdbm_utf16_ci!("LOADLIBRARYW"), sdbm_utf16_ci(&wide("loadlibraryw")));
+    assert_ne!(ct_sdbm_utf16!("Größe"), sdbm_hash("Größe"));
+}
+
+#[test]
+fn siphash24_vectors() {
+    let k0 = u64::from_le_bytes([0, 1, 2, 3, 4, 5, 6, 7]);
+    let k1 = u64::from_le_bytes([8, 9, 10, 11, 12, 13, 14, 15]);
+    let msg: Vec<u8> = (0..64).collect();
+    assert_eq!(siphash24(k0, k1, &msg[..0]), 0x726fdb47dd0e0e31);
+    assert_eq!(siphash24(k0, k1, &msg[..1]), 0x74f839c593dc67fd);
+    assert_eq!(siphash24(k0, k1, &msg[..8]), 0x93f5f5799a932462);
+    assert_eq!(siphash24(k0, k1, &msg[..15]), 0xa129ca6149be45e5);
+    assert_eq!(siphash24(k0, k1, &msg[..63]), 0x958a324ceb064572);
+    const AT_COMPILE: u64 = siphash24(0, 0, b"abc");
+    assert_eq!(AT_COMPILE, siphash24(0, 0, b"abc"));
+}
@@ -0,0 +1,66 @@
+use rust_native_obf::*;
+
+const APIS: CtHashSet<6> = ct_hash_set!["ptrace", "getpid", "mprotect", "dlopen", "open", "read"];
+const SDBM: CtHashSet<3> = ct_hash_set![sdbm; "LoadLibraryA", "GetProcAddress", "VirtualProtect"];
+const PERFECT: PerfectHashSet<6, 32> = ct_perfect_hash_set!["ptrace", "getpid", "mprotect", "dlopen", "open", "read"];
+
+const _: () = assert!(APIS.contains(ct_xxh32!(b"ptrace")));
+const MPROTECT_AT: Option<usize> = APIS.position(ct_xxh32!(b"mprotect"));
+
+#[test]
+fn hashes_are_sorted_and_unique() {
+    assert!(APIS.hashes().windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(APIS.len(), 6);
+    assert!(!APIS.is_empty());
+    assert!(ct_hash_set![].is_empty());
+}
+
+#[test]
+fn lookup_returns_declaration_order() {
+    assert_eq!(MPROTECT_AT, Some(2));
+    for (i, name) in ["ptrace", "getpid", "mprotect", "dlopen", "open", "read"].iter().enumerate() {
+        assert_eq!(APIS.position(xxh32(name.as_bytes(), 0)), Some(i));
+        assert_eq!(APIS.position_of(name), Some(i));
+        assert_eq!(PERFECT.position_of(name), Some(i));
+    }
+    assert!(!APIS.contains(ct_xxh32!(b"write")));
+    assert!(!APIS.contains_name("write"));
+    assert_eq!(PERFECT.position(ct_xxh32!(b"write")), None);
+}
+
+#[test]
+fn sdbm_sets_match_ct_sdbm() {
+    assert_eq!(SDBM.kind(), HashKind::Sdbm);
+    assert_eq!(SDBM.position(ct_sdbm!("GetProcAddress")), Some(1));
+    assert!(SDBM.contains(sdbm_hash("VirtualProtect")));
+    assert!(!SDBM.contains(xxh32(b"VirtualProtect", 0)));
+}
+
+#[test]
+fn perfect_set_finds_a_seed() {
+    let explicit = APIS.perfect::<8>();
+    for name in ["ptrace", "read", "open"] {
+        assert_eq!(explicit.position_of(name), APIS.position_of(name));
+    }
+    assert!(PERFECT.contains(ct_xxh32!(b"dlopen")));
+    assert_eq!(*PERFECT.set().hashes(), *APIS.hashes());
+}
+
+#[test]
+#[should_panic(expected = "two names hash to the same value")]
+fn duplicate_names_are_rejected() {
+    CtHashSet::<3>::new(HashKind::Sdbm, &["a", "b", "a"]);
+}
+
+#[test]
+#[should_panic(expected = "two names hash to the same value")]
+fn colliding_names_are_rejected() {
+    assert_eq!(sdbm_hash("hQO5qgDu"), sdbm_hash("mizBip8c"));
+    CtHashSet::<2>::new(HashKind::Sdbm, &["hQO5qgDu", "mizBip8c"]);
+}
+
+#[test]
+#[should_panic(expected = "name count does not match")]
+fn wrong_name_count_is_rejected() {
+    CtHashSet::<2>::new(HashKind::Xxh32, &["ptrace"]);
+}
@@ -0,0 +1,63 @@
+#![cfg(all(target_os = "linux", target_pointer_width = "64"))]
+
+use rust_native_obf::hash::sdbm_hash;
+use rust_native_obf::import::{resolve, resolve_sdbm, resolve_xxhash, SymbolHash};
+use rust_native_obf::*;
+
+use core::ffi::{c_char, c_int, c_long, c_void};
+
+obf_import! {
+    fn getpid() -> i32;
+    fn getppid() -> i32;
+    fn strlen(s: *const c_char) -> usize;
+    fn snprintf(buf: *mut c_char, len: usize, fmt: *const c_char, ...) -> c_int;
+    fn ptrace(request: c_int, ...) -> c_long
+}
+
+extern "C" {
+    fn dlsym(handle: *mut c_void, name: *const c_char) -> *mut c_void;
+}
+
+#[test]
+fn resolves_by_either_hash() {
+    let by_xxh = resolve_xxhash(ct_xxh32!(b"getpid")).unwrap();
+    let by_sdbm = resolve_sdbm(sdbm_hash("getpid")).unwrap();
+    assert_eq!(by_xxh, by_sdbm);
+    assert_eq!(resolve(SymbolHash::Xxh32(ct_xxh32!(b"getpid"))), Some(by_xxh));
+}
+
+#[test]
+fn unknown_symbol_is_none() {
+    assert_eq!(resolve_xxhash(ct_xxh32!(b"no_such_symbol_anywhere_42")), None);
+}
+
+#[test]
+fn typed_imports_call_through() {
+    unsafe {
+        assert_eq!(getpid() as u32, std::process::id());
+        assert!(getppid() > 0);
+        assert_eq!(strlen(c"obfuscated".as_ptr()), 10);
+        assert_eq!(getpid() as u32, std::process::id());
+    }
+}
+
+#[test]
+fn variadic_imports_call_through() {
+    unsafe {
+        let mut buf = [0 as c_char; 32];
+        let n = snprintf()(buf.as_mut_ptr(), buf.len(), c"%d:%s:%ld".as_ptr(), 42 as c_int, c"ok".as_ptr(), -7 as c_long);
+        assert_eq!(n, 8);
+        assert_eq!(core::ffi::CStr::from_ptr(buf.as_ptr()), c"42:ok:-7");
+        let peek_bad_pid = ptrace()(0x4206, -1 as c_int, 0usize, 0usize);
+        assert_eq!(peek_bad_pid, -1);
+    }
+}
+
+#[test]
+fn ifuncs_resolve_like_the_loader() {
+    for name in [c"strlen", c"memchr", c"memcpy", c"strchr"] {
+        let ours = resolve_xxhash(xxh32(name.to_bytes(), 0)).unwrap();
+        let loader = unsafe { dlsym(core::ptr::null_mut(), name.as_ptr()) };
+        assert_eq!(ours as usize, loader as usize, "{:?}", name);
+    }
+}
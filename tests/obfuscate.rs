@@ -0,0 +1,125 @@
+use rust_native_obf::*;
+
+#[obfuscate]
+fn name() -> &'static str {
+    "alice"
+}
+
+struct User {
+    nick: String,
+}
+
+impl User {
+    #[obfuscate]
+    fn label(&self) -> &str {
+        if self.nick.is_empty() { "anonymous" } else { &self.nick }
+    }
+}
+
+#[obfuscate]
+fn table() -> &'static [u32] {
+    &[1, 2, 3]
+}
+
+#[obfuscate]
+fn wide() -> u128 {
+    let x: u128 = 5;
+    let big: u128 = 340282366920938463463374607431768211455;
+    let signed: i128 = 7i128;
+    x + (big >> 120) + signed as u128
+}
+
+#[obfuscate]
+fn classify(n: i64) -> &'static str {
+    let limit = 100;
+    let scaled = n.checked_mul(3).unwrap_or(0);
+    if scaled > limit {
+        "big"
+    } else {
+        #[no_obf]
+        let fallback = "small";
+        if n < 0 { "negative" } else { fallback }
+    }
+}
+
+fn first_of<const N: usize>(buf: [u8; N]) -> u8 {
+    buf[0]
+}
+
+#[obfuscate]
+fn sized() -> usize {
+    let mut buf: [u8; 16] = [0; 16];
+    buf[3] = 9;
+    let head = first_of::<4>([1, 2, 3, 4]);
+    let grid: Vec<[u16; 2]> = vec![[1, 2]; 3];
+    buf.len() + buf[3] as usize + head as usize + grid.len()
+}
+
+const LIMIT: u32 = 40;
+
+#[obfuscate]
+fn bucket(n: u32, tag: Option<&str>) -> u32 {
+    let base = match n {
+        0 => 1,
+        1..=5 => 2,
+        6 | 7 => 3,
+        LIMIT => 4,
+        x if x > 100 => 5,
+        _ => 6,
+    };
+    let bonus = match tag {
+        Some("vip") => 10,
+        Some(_) | None => 0,
+    };
+    if let (1..=9, [b'a', ..]) = (n, tag.unwrap_or("-").as_bytes()) { base + bonus + 100 } else { base + bonus }
+}
+
+#[obfuscate(strings, level = 3)]
+fn banner(user: &str) -> String {
+    let greeting = "hello";
+    format!("{} {}", greeting, user)
+}
+
+#[obfuscate(level = 0)]
+fn untouched() -> (&'static str, u8) {
+    ("plain", 255)
+}
+
+#[test]
+fn static_positions_compile() {
+    assert_eq!(name(), "alice");
+    assert_eq!(name(), "alice");
+    assert_eq!(table(), &[1, 2, 3]);
+    assert_eq!(User { nick: String::new() }.label(), "anonymous");
+    assert_eq!(User { nick: "zed".into() }.label(), "zed");
+}
+
+#[test]
+fn wide_literals() {
+    assert_eq!(wide(), 5 + 255 + 7);
+}
+
+#[test]
+fn patterns_stay_plain() {
+    assert_eq!(bucket(0, None), 1);
+    assert_eq!(bucket(3, Some("vip")), 12);
+    assert_eq!(bucket(3, Some("abc")), 102);
+    assert_eq!(bucket(7, None), 3);
+    assert_eq!(bucket(40, Some("x")), 4);
+    assert_eq!(bucket(500, None), 5);
+    assert_eq!(bucket(20, None), 6);
+}
+
+#[test]
+fn array_lengths_stay_const() {
+    assert_eq!(sized(), 16 + 9 + 1 + 3);
+}
+
+#[test]
+fn rewritten_bodies_behave() {
+    assert_eq!(classify(50), "big");
+    assert_eq!(classify(10), "small");
+    assert_eq!(classify(-1), "negative");
+    assert_eq!(banner("bob"), "hello bob");
+    assert_eq!(untouched(), ("plain", 255));
+}
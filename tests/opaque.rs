@@ -0,0 +1,45 @@
+use rust_native_obf::opaque::*;
+use rust_native_obf::*;
+
+const ALL: [fn() -> bool; PREDICATES as usize] =
+    [consecutive_product, square_residue, seven_square, pointer_alias, odd_state, pair_state];
+
+#[test]
+fn every_predicate_holds() {
+    for (i, pred) in ALL.iter().enumerate() {
+        for round in 0..100_000 {
+            assert!(pred(), "predicate {} failed on call {}", i, round);
+        }
+    }
+}
+
+#[test]
+fn picks_and_macro_hold() {
+    for _ in 0..10_000 {
+        assert!(opaque_pick::<0>());
+        assert!(opaque_pick::<1>());
+        assert!(opaque_pick::<2>());
+        assert!(opaque_pick::<3>());
+        assert!(opaque_pick::<4>());
+        assert!(opaque_pick::<5>());
+        assert!(opaque_pick::<{ u8::MAX }>());
+        assert!(opaque_pred!());
+        assert!(opaque_pred!("other site"));
+        assert!(opaque_true());
+        assert_eq!(opaque_identity(7), 7);
+    }
+}
+
+#[test]
+fn predicates_hold_across_threads() {
+    std::thread::scope(|s| {
+        for t in 0..8 {
+            s.spawn(move || {
+                for round in 0..20_000 {
+                    let pred = ALL[(t + round) % ALL.len()];
+                    assert!(pred(), "thread {} call {}", t, round);
+                }
+            });
+        }
+    });
+}
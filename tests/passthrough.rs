@@ -0,0 +1,108 @@
+use rust_native_obf::*;
+
+static TABLE: [u32; 4] = [3, 1, 4, 1];
+
+fn double(x: u32) -> u32 {
+    x * 2
+}
+
+fn classify(n: i64) -> &'static str {
+    obf_if!(n < 0, { "negative" }, { obf_if!(n == 0, { "zero" }, { "positive" }) })
+}
+
+fn select(n: i64) -> u8 {
+    obf_if!(branchless n > 10, { 1 }, { 0 })
+}
+
+#[test]
+fn strings_match_plain() {
+    let s: &str = obf_str!("hello, world!");
+    assert_eq!(s, "hello, world!");
+    assert_eq!(s.len(), 13);
+    let leveled = obf_str!(level = 3; "three");
+    assert_eq!(leveled.to_owned(), "three");
+}
+
+#[test]
+fn constants_match_plain() {
+    let a: u32 = obf_const!(0xdead_beef, u32);
+    let b: u64 = obf_const!(level = 3; 0x0123_4567_89ab_cdef, u64);
+    let c: u16 = obf_const!(4242, u16);
+    let d: i32 = obf_const!(77);
+    assert_eq!((a, b, c, d), (0xdead_beef, 0x0123_4567_89ab_cdef, 4242, 77));
+}
+
+#[test]
+fn control_flow_matches_plain() {
+    assert_eq!(classify(-5), "negative");
+    assert_eq!(classify(0), "zero");
+    assert_eq!(classify(9), "positive");
+    assert_eq!((select(11), select(10)), (1, 0));
+
+    let mut hits = 0;
+    for i in 0..10 {
+        obf_if!(i % 3 == 0, { hits += 1; });
+    }
+    assert_eq!(hits, 4);
+
+    let called: u32 = hidden_call!(double(21));
+    assert_eq!(called, 42);
+    let block: u32 = obf_block!({ double(called) + 1 });
+    assert_eq!(block, 85);
+    let leveled: u32 = obf_block!(level = 3; { block - 5 });
+    assert_eq!(leveled, 80);
+}
+
+#[test]
+fn static_ref_points_at_static() {
+    let table: &'static [u32; 4] = obf_static_ref!(&TABLE);
+    assert!(core::ptr::eq(table, &TABLE));
+    assert_eq!(table.iter().sum::<u32>(), 9);
+}
+
+#[test]
+fn debug_trap_is_unit() {
+    let unit: () = debug_trap!();
+    assert_eq!(unit, ());
+    debug_trap!(level = 0);
+}
+
+fn flat_sum(items: &[u32]) -> Option<u32> {
+    let mut total = 0u32;
+    for &item in items {
+        obf_flatten! { in loop;
+            if item == 0 {
+                continue;
+            }
+            total = total.checked_add(item)?;
+        }
+    }
+    obf_flatten! {
+        let doubled = total * 2;
+        Some(doubled)
+    }
+}
+
+#[test]
+fn remaining_macros_match_plain() {
+    assert_eq!(obf_bytes!(b"bytes"), *b"bytes");
+    assert_eq!(obf_bytes!(level = 3; b"xyz"), *b"xyz");
+    let wide: Vec<u16> = "wide ✓".encode_utf16().collect();
+    assert_eq!(obf_wide!("wide ✓").as_slice(), wide.as_slice());
+    assert_eq!(obf_wide!(level = 0; "w").as_slice(), &[b'w' as u16]);
+
+    assert_eq!(flat_sum(&[1, 0, 2, 3]), Some(12));
+    assert_eq!(flat_sum(&[u32::MAX, 1]), None);
+
+    let checked: u32 = obf_bogus!([LIMIT: u32 = 10] { if double(3) > LIMIT { 1 } else { 0 } });
+    assert_eq!(checked, 0);
+    let bare: u32 = obf_bogus!({ double(4) });
+    assert_eq!(bare, 8);
+    assert_eq!(obf_call!(double(5)), 10);
+}
+
+#[cfg(feature = "passthrough")]
+#[test]
+fn passthrough_forces_level_zero() {
+    assert_eq!(level::OBF_LEVEL, 0);
+}
@@ -0,0 +1,201 @@
+use rust_native_obf::pointer::ObfFn;
+use rust_native_obf::*;
+
+fn mul_xor(a: u32, b: u32) -> u32 {
+    a.wrapping_mul(b) ^ 0x1234
+}
+
+unsafe fn raw_read(p: *const u64) -> u64 {
+    unsafe { *p }
+}
+
+mod nested {
+    pub fn greet(name: &str) -> String {
+        format!("hi {}", name)
+    }
+}
+
+#[test]
+fn obf_call_routes_through_slot() {
+    for i in 0..4u32 {
+        assert_eq!(obf_call!(mul_xor(i, 7)), mul_xor(i, 7));
+    }
+    assert_eq!(obf_call!(nested::greet("bob")), "hi bob");
+    let v = 0xfeedu64;
+    assert_eq!(unsafe { obf_call!(unsafe raw_read(&v)) }, 0xfeed);
+}
+
+struct Scaler<T>(T);
+
+impl<T: Copy + Into<u64>> Scaler<T> {
+    fn helper(x: u64) -> u64 {
+        x * 3
+    }
+
+    fn run(&self) -> u64 {
+        obf_call!(Self::helper(self.0.into()))
+    }
+}
+
+#[test]
+fn obf_call_resolves_self_paths() {
+    assert_eq!(Scaler(5u32).run(), 15);
+    assert_eq!(Scaler(7u8).run(), 21);
+}
+
+#[test]
+fn obf_fn_stores_mangled_address() {
+    let f: fn(u32, u32) -> u32 = mul_xor;
+    let obf = ObfFn::<_, 0x5eed>::new(f);
+    assert_ne!(obf.encoded(), f as usize);
+    assert_eq!((obf.get())(3, 4), mul_xor(3, 4));
+}
+
+struct Counted<'a>(&'a std::cell::Cell<u32>, u32);
+
+impl Drop for Counted<'_> {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() + 1);
+    }
+}
+
+#[test]
+fn obf_ptr_stores_mangled_address() {
+    let mut x = 42u64;
+    let raw = &mut x as *mut u64;
+    let mut p = ObfPtr::new(raw);
+    assert_ne!(p.encoded(), raw as usize);
+    assert_eq!(p.get(), raw);
+    assert_eq!(unsafe { p.as_ref() }, Some(&42));
+    unsafe { *p.as_mut().unwrap() = 7 };
+    assert_eq!(x, 7);
+    p.set(core::ptr::null_mut());
+    assert!(p.is_null());
+    assert_ne!(ObfPtr::<u64>::null().encoded(), 0);
+}
+
+#[test]
+fn obf_box_drops_once() {
+    let drops = std::cell::Cell::new(0);
+    let mut b = ObfBox::new(Counted(&drops, 1));
+    b.1 = 2;
+    assert_eq!(b.1, 2);
+    let c = ObfBox::new(Counted(&drops, 3));
+    drop(b);
+    assert_eq!(drops.get(), 1);
+    let inner = c.into_inner();
+    assert_eq!(drops.get(), 1);
+    assert_eq!(inner.1, 3);
+    drop(inner);
+    assert_eq!(drops.get(), 2);
+    let raw = Box::new(Counted(&drops, 4));
+    let back = ObfBox::from_box(raw).into_box();
+    assert_eq!(back.1, 4);
+    drop(back);
+    assert_eq!(drops.get(), 3);
+}
+
+#[test]
+fn obf_box_clone_is_deep() {
+    let a = ObfBox::new(vec![1, 2, 3]);
+    let mut b = a.clone();
+    b.push(4);
+    assert_eq!(*a, [1, 2, 3]);
+    assert_eq!(*b, [1, 2, 3, 4]);
+}
+
+#[test]
+fn obf_rc_tracks_refcount() {
+    let drops = std::cell::Cell::new(0);
+    let a = ObfRc::new(Counted(&drops, 9));
+    assert_eq!(ObfRc::strong_count(&a), 1);
+    let b = a.clone();
+    assert_eq!(ObfRc::strong_count(&a), 2);
+    assert!(ObfRc::ptr_eq(&a, &b));
+    assert_eq!(b.1, 9);
+    drop(a);
+    assert_eq!(ObfRc::strong_count(&b), 1);
+    assert_eq!(drops.get(), 0);
+    let rc = b.into_rc();
+    assert_eq!(std::rc::Rc::strong_count(&rc), 1);
+    let c = ObfRc::from_rc(rc.clone());
+    assert_eq!(ObfRc::strong_count(&c), 2);
+    drop(rc);
+    drop(c);
+    assert_eq!(drops.get(), 1);
+}
+
+static COUNTER: ObfStatic<u64> = ObfStatic::new(0);
+
+#[test]
+fn obf_static_lock_and_with_mut() {
+    let cell = ObfStatic::new(vec![1u8]);
+    cell.lock().push(2);
+    cell.with_mut(|v| v.push(3));
+    assert_eq!(cell.with(|v| v.len()), 3);
+    assert_eq!(*cell.lock(), [1, 2, 3]);
+    assert_eq!(cell.into_inner(), [1, 2, 3]);
+}
+
+#[test]
+fn obf_static_try_lock_contends() {
+    let cell = ObfStatic::new(5u32);
+    let guard = cell.lock();
+    assert!(cell.try_lock().is_none());
+    std::thread::scope(|s| {
+        s.spawn(|| assert!(cell.try_lock().is_none())).join().unwrap();
+    });
+    drop(guard);
+    *cell.try_lock().unwrap() += 1;
+    assert_eq!(*cell.lock(), 6);
+}
+
+#[test]
+fn obf_static_serialises_threads() {
+    std::thread::scope(|s| {
+        for _ in 0..8 {
+            s.spawn(|| {
+                for _ in 0..1000 {
+                    COUNTER.with_mut(|c| *c += 1);
+                }
+            });
+        }
+    });
+    assert_eq!(*COUNTER.lock(), 8000);
+}
+
+obf_static! {
+    static SECRET: [u32; 3] = [0xdead, 0xbeef, 7];
+}
+
+#[test]
+fn obf_static_round_trips_writes() {
+    assert_eq!(SECRET.get(), [0xdead, 0xbeef, 7]);
+    SECRET.with_mut(|v| v[2] = 9);
+    assert_eq!(SECRET.with(|v| v[2]), 9);
+    SECRET.set([1, 2, 3]);
+    assert_eq!(*SECRET.lock(), [1, 2, 3]);
+}
+
+obf_static! {
+    static ENDPOINTS: [FixedStr<32>; 2] = [FixedStr::new("https://api.example.com"), FixedStr::new("https://cdn.example.com")];
+}
+
+#[test]
+fn obf_static_holds_fixed_strings() {
+    let raw = unsafe {
+        core::slice::from_raw_parts(&ENDPOINTS as *const _ as *const u8, core::mem::size_of_val(&ENDPOINTS))
+    };
+    assert!(!raw.windows(11).any(|w| w == b"api.example"));
+    assert_eq!(&*ENDPOINTS.get()[0], "https://api.example.com");
+    ENDPOINTS.with_mut(|e| e[1] = FixedStr::new("https://backup.example.com"));
+    let hosts = ENDPOINTS.get();
+    assert_eq!(hosts.map(|h| h.len()), [23, 26]);
+    assert_eq!(hosts[1].as_str(), "https://backup.example.com");
+}
+
+#[test]
+#[should_panic(expected = "FixedStr: string does not fit")]
+fn fixed_str_rejects_long_input() {
+    FixedStr::<4>::new("too long");
+}
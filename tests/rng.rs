@@ -0,0 +1,121 @@
+use rust_native_obf::rng::{rand_below, rand_char, rand_f32, rand_f64, rand_nth, rand_range, range_fits};
+use rust_native_obf::*;
+
+const KEY: [u8; 32] = ct_rand_array!(u8, 32);
+const WIDE: u128 = ct_rand!(u128);
+const SIGNED: i128 = ct_rand!(i128);
+const UNIT: f64 = ct_rand!(f64);
+const UNIT32: f32 = ct_rand!(f32);
+const LETTER: char = ct_rand!(char);
+const DIE: u8 = ct_rand_range!(u8, 1..7);
+const OFFSET: i16 = ct_rand_range!(i16, -300..-100);
+
+#[test]
+fn extended_types() {
+    assert!((0.0..1.0).contains(&UNIT));
+    assert!((0.0..1.0).contains(&UNIT32));
+    assert!(!(0xd800..0xe000).contains(&(LETTER as u32)));
+    assert_ne!(WIDE >> 64, 0);
+    assert_ne!(SIGNED, 0);
+    assert!((1..7).contains(&DIE));
+    assert!((-300..-100).contains(&OFFSET));
+    let inferred: u64 = ct_rand_range!(1_000..5_000_000_000);
+    assert!((1_000..5_000_000_000).contains(&inferred));
+    let _full: u8 = ct_rand_range!(0..256);
+    let signed: i8 = ct_rand_range!(-128..0);
+    let wide: u128 = ct_rand_range!(0..2);
+    assert!(signed < 0);
+    assert!(wide < 2);
+}
+
+#[test]
+fn range_bounds_are_checked_per_type() {
+    assert!(!range_fits(&0u128, &(-5..5)));
+    assert!(!range_fits(&0u8, &(0..257)));
+    assert!(!range_fits(&0i8, &(-129..0)));
+    assert!(range_fits(&0u128, &(0..i128::MAX)));
+    assert!(range_fits(&0i128, &(i128::MIN..0)));
+    let top: u128 = ct_rand_range!(u128, 5..6);
+    let edge: i64 = ct_rand_range!(i64, -1..0);
+    assert_eq!((top, edge), (5, -1));
+}
+
+#[test]
+fn arrays_are_distinct_per_site() {
+    let other: [u8; 32] = ct_rand_array!(u8, 32);
+    assert_ne!(KEY, other);
+    assert_ne!(KEY, [KEY[0]; 32]);
+    assert_ne!(ct_rand_array!(u64, 4, "a"), ct_rand_array!(u64, 4, "b"));
+    let floats: [f64; 16] = ct_rand_array!(f64, 16);
+    assert!(floats.iter().all(|f| (0.0..1.0).contains(f)));
+}
+
+#[test]
+fn float_bounds() {
+    assert_eq!(rand_f64(0), 0.0);
+    assert!(rand_f64(u64::MAX) < 1.0);
+    assert!(rand_f32(u64::MAX) < 1.0);
+}
+
+#[test]
+fn chars_cover_the_whole_range() {
+    let mut high = false;
+    for i in 0..10_000 {
+        let c = rand_char(rand_nth(7, i));
+        high |= c as u32 > 0xe000;
+    }
+    assert!(high);
+}
+
+#[test]
+fn ranges_are_unbiased() {
+    let mut counts = [0u32; 3];
+    for i in 0..30_000 {
+        counts[rand_below(rand_nth(42, i), 3) as usize] += 1;
+    }
+    assert!(counts.iter().all(|&c| (9_400..10_600).contains(&c)), "{:?}", counts);
+    for i in 0..1_000 {
+        assert!((-5..5).contains(&rand_range(i, -5, 5)));
+        assert_eq!(rand_range(i, i128::MIN, i128::MIN + 1), i128::MIN);
+    }
+}
+
+const ORDER: Permutation<16> = ct_shuffle!(16);
+const LAYOUT: Permutation<6> = ct_shuffle!(6);
+const TABLE: [u32; 6] = LAYOUT.apply([10, 20, 30, 40, 50, 60]);
+
+#[test]
+fn shuffles_are_inverse_pairs() {
+    let mut seen = [false; 16];
+    for i in 0..16 {
+        seen[ORDER.get(i)] = true;
+        assert_eq!(ORDER.position(ORDER.get(i)), i);
+        assert_eq!(ORDER.forward[ORDER.inverse[i]], i);
+    }
+    assert!(seen.iter().all(|&s| s));
+    assert_ne!(ORDER.forward, core::array::from_fn(|i| i));
+    assert_ne!(ct_shuffle!(16, "a"), ct_shuffle!(16, "b"));
+
+    assert_eq!(LAYOUT.unapply(TABLE), [10, 20, 30, 40, 50, 60]);
+    assert_eq!(TABLE[LAYOUT.position(2)], 30);
+    assert_eq!(ct_permutation::<0>(1).forward, []);
+    assert_eq!(ct_permutation::<1>(1).inverse, [0]);
+}
+
+#[test]
+fn permutations_are_uniform() {
+    let mut counts = [0u32; 6];
+    for seed in 0..6_000u64 {
+        let p = ct_permutation::<3>(xxhash_mix(seed));
+        let rank = match p.forward {
+            [0, 1, 2] => 0,
+            [0, 2, 1] => 1,
+            [1, 0, 2] => 2,
+            [1, 2, 0] => 3,
+            [2, 0, 1] => 4,
+            _ => 5,
+        };
+        counts[rank] += 1;
+    }
+    assert!(counts.iter().all(|&c| (850..1_150).contains(&c)), "{:?}", counts);
+}
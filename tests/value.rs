@@ -0,0 +1,194 @@
+use rust_native_obf::*;
+
+fn image<T: NoUninit>(v: &ObfuscatedValue<T>) -> Vec<u8> {
+    let len = core::mem::size_of_val(v);
+    unsafe { core::slice::from_raw_parts(v as *const ObfuscatedValue<T> as *const u8, len) }.to_vec()
+}
+
+#[test]
+fn set_update_replace() {
+    let mut v = ObfuscatedValue::new(100u32);
+    v.set(99);
+    assert_eq!(v.get(), 99);
+    assert_eq!(v.update(|x| x * 2), 198);
+    assert_eq!(v.get(), 198);
+    assert_eq!(v.replace(5), 198);
+    assert_eq!(v.get(), 5);
+}
+
+#[test]
+fn swap_exchanges_values() {
+    let mut a = ObfuscatedValue::new([1u8, 2, 3]);
+    let mut b = ObfuscatedValue::new([4u8, 5, 6]);
+    a.swap(&mut b);
+    assert_eq!(a.get(), [4, 5, 6]);
+    assert_eq!(b.get(), [1, 2, 3]);
+}
+
+#[test]
+fn with_borrows_plaintext() {
+    let v = ObfuscatedValue::new([7u64; 4]);
+    assert_eq!(v.with(|arr| arr.iter().sum::<u64>()), 28);
+    assert_eq!(v.get(), [7; 4]);
+}
+
+#[test]
+fn every_write_rekeys() {
+    let mut v = ObfuscatedValue::new(0x1122_3344_5566_7788u64);
+    let mut seen = vec![image(&v)];
+    v.set(0x1122_3344_5566_7788);
+    seen.push(image(&v));
+    v.update(|x| x);
+    seen.push(image(&v));
+    let mut w = ObfuscatedValue::new(0x1122_3344_5566_7788u64);
+    v.swap(&mut w);
+    seen.push(image(&v));
+    seen.push(image(&w));
+    for (i, a) in seen.iter().enumerate() {
+        assert!(!seen[i + 1..].contains(a));
+    }
+    assert_eq!(v.get(), w.get());
+}
+
+#[test]
+fn same_value_differs_per_instance() {
+    let a = ObfuscatedValue::new(42u64);
+    let b = ObfuscatedValue::new(42u64);
+    assert_ne!(image(&a), image(&b));
+    assert_eq!(a.get(), b.get());
+    assert_eq!(format!("{:?}", a), "ObfuscatedValue(<redacted>)");
+}
+
+#[test]
+fn stored_words_do_not_reveal_the_value() {
+    for val in [0u64, 1, 100, 0x1122_3344_5566_7788, u64::MAX] {
+        let v = ObfuscatedValue::new(val);
+        let words: Vec<u64> = image(&v).chunks_exact(8).map(|c| u64::from_ne_bytes(c.try_into().unwrap())).collect();
+        assert!(!words.contains(&val));
+        for (i, a) in words.iter().enumerate() {
+            for b in &words[i + 1..] {
+                assert_ne!(a ^ b, val);
+            }
+        }
+    }
+}
+
+static HOOK_HITS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+static GLOBAL_HITS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+fn count_hook(event: &TamperEvent) {
+    assert_eq!(event.copies, 3);
+    HOOK_HITS.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+}
+
+fn count_global(_: &TamperEvent) {
+    GLOBAL_HITS.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+}
+
+fn silent(_: &TamperEvent) {}
+
+#[test]
+fn guarded_stays_intact() {
+    let mut g = GuardedValue::<_, 3>::new([1u8, 2, 3]);
+    for _ in 0..64 {
+        assert!(g.is_intact());
+        g.update(|v| [v[1], v[2], v[0]]);
+    }
+    let c = GuardedValue::<char>::new('é');
+    let b = GuardedValue::<bool>::new(true);
+    assert_eq!((c.try_get(), b.try_get()), (Ok('é'), Ok(true)));
+}
+
+#[test]
+fn guarded_detects_tampered_copy() {
+    let mut g = GuardedValue::<u64, 3>::with_hook(500, count_hook);
+    g.corrupt_copy(1);
+    assert_eq!(g.try_get(), Err(TamperEvent { copies: 3, intact: 2 }));
+    assert!(!g.is_intact());
+    let before = HOOK_HITS.load(std::sync::atomic::Ordering::SeqCst);
+    assert_eq!(g.get(), 500);
+    assert_eq!(HOOK_HITS.load(std::sync::atomic::Ordering::SeqCst), before + 1);
+    g.set(501);
+    assert_eq!(g.try_get(), Ok(501));
+}
+
+#[test]
+fn guarded_detects_flipped_bytes() {
+    let mut g = GuardedValue::<u64>::with_hook(0x55, silent);
+    g.corrupt_copy(0);
+    assert_eq!(g.try_get(), Err(TamperEvent { copies: 2, intact: 1 }));
+    assert_eq!(g.get(), 0x55);
+}
+
+#[test]
+#[should_panic(expected = "0 of 2 copies intact")]
+fn guarded_panics_when_no_copy_matches() {
+    let mut g = GuardedValue::<u32>::with_hook(1, silent);
+    g.corrupt_copy(0);
+    g.corrupt_copy(1);
+    g.get();
+}
+
+#[test]
+fn global_hook_runs_without_instance_hook() {
+    set_tamper_hook(count_global);
+    let mut g = GuardedValue::<u32>::new(10);
+    g.corrupt_copy(1);
+    assert_eq!(g.get(), 10);
+    assert_eq!(GLOBAL_HITS.load(std::sync::atomic::Ordering::SeqCst), 1);
+}
+
+#[test]
+fn atomics_fetch_add_across_threads() {
+    use std::sync::atomic::Ordering::*;
+    let a = ObfAtomicU64::new(0);
+    let b = ObfAtomicU32::new(0);
+    let c = ObfAtomicUsize::new(0);
+    std::thread::scope(|s| {
+        for _ in 0..8 {
+            s.spawn(|| {
+                for _ in 0..10_000 {
+                    a.fetch_add(1, SeqCst);
+                    b.fetch_add(2, AcqRel);
+                    c.fetch_sub(1, Relaxed);
+                }
+            });
+        }
+    });
+    assert_eq!(a.load(SeqCst), 80_000);
+    assert_eq!(b.into_inner(), 160_000);
+    assert_eq!(c.load(Acquire), 0usize.wrapping_sub(80_000));
+}
+
+#[test]
+fn atomics_compare_exchange_across_threads() {
+    use std::sync::atomic::Ordering::*;
+    let counter = ObfAtomicU32::new(0);
+    let claimed = ObfAtomicU64::new(0);
+    std::thread::scope(|s| {
+        for t in 0..8u32 {
+            let (counter, claimed) = (&counter, &claimed);
+            s.spawn(move || {
+                for _ in 0..5_000 {
+                    let mut cur = counter.load(Relaxed);
+                    loop {
+                        match counter.compare_exchange_weak(cur, cur + 1, AcqRel, Relaxed) {
+                            Ok(prev) => {
+                                assert_eq!(prev, cur);
+                                break;
+                            }
+                            Err(actual) => cur = actual,
+                        }
+                    }
+                }
+                assert_eq!(claimed.fetch_or(1 << t, SeqCst) & (1 << t), 0);
+            });
+        }
+    });
+    assert_eq!(counter.load(SeqCst), 40_000);
+    assert_eq!(claimed.load(SeqCst), 0xff);
+    assert_eq!(counter.compare_exchange(1, 2, SeqCst, SeqCst), Err(40_000));
+    assert_eq!(counter.swap(7, SeqCst), 40_000);
+    assert_eq!(counter.fetch_max(3, SeqCst), 7);
+    assert_eq!(counter.load(SeqCst), 7);
+}